@@ -22,6 +22,13 @@ if ! command -v jq &> /dev/null; then
   exit 1
 fi
 
+# Runtime archives without a pinned checksum can't be installed
+if jq -e '[.[][][] | select(.sha256 == "")] | length > 0' src-tauri/runtimes.json > /dev/null; then
+  echo "Error: src-tauri/runtimes.json has archives without a checksum."
+  echo "Run ./scripts/update-runtime-checksums and commit the result."
+  exit 1
+fi
+
 # Check if the x86_64-apple-darwin target is installed
 if ! rustup target list --installed | grep -q "x86_64-apple-darwin"; then
   echo "Installing x86_64-apple-darwin target..."
@@ -1,14 +1,16 @@
 use crate::clients::{self, ClientPathConfig, ClientType};
-use crate::environment::{ensure_environment_sync, ensure_npx_shim, get_uvx_path};
+use crate::environment::{check_shim, ensure_npx_shim, ensure_ready, ensure_uvx_shim, ShimHealth};
 use crate::file_utils::{ensure_config_file, ensure_mcp_servers};
+use crate::registry::load_registry_apps;
 use dirs;
 use lazy_static::lazy_static;
 use log::{debug, error, info, warn};
 use regex;
-use reqwest::blocking::get;
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
 
@@ -23,7 +25,6 @@ lazy_static! {
         Mutex::new(std::collections::HashMap::new());
     static ref TEST_CONFIG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
     pub static ref APP_REGISTRY_CACHE: Mutex<Option<Value>> = Mutex::new(None);
-    static ref ENV_SETUP_COMPLETE: Mutex<bool> = Mutex::new(false);
 }
 
 // Initialize default path configurations for supported clients
@@ -50,7 +51,7 @@ pub fn validate_client(client: &ClientType) -> Result<(), String> {
     clients::validate_client(client)
 }
 
-fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
+pub(crate) fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
     debug!(
         "Getting config path for client {}, test_mode: {}",
         client.as_str(),
@@ -84,11 +85,23 @@ fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub mcp_key: String,
+    pub runtime: String,
     pub command: String,
     pub args: Vec<String>,
+    // Why the app can't run on this machine, if it can't
+    pub unavailable_reason: Option<String>,
+    // Set for containerized apps, `command` is then the container engine
+    pub container: Option<crate::registry::ContainerSpec>,
+    // Set for prebuilt binaries, `command` is then where the download is kept
+    pub download: Option<crate::registry::BinaryDownload>,
+    // Set for apps built from a git checkout
+    pub git: Option<crate::registry::GitSource>,
+    // Node.js version of npx apps or `--python` request of uvx apps whose
+    // registry entry asks for a specific range
+    pub runtime_version: Option<String>,
 }
 
-fn fetch_app_registry() -> Result<Value, String> {
+pub(crate) fn fetch_app_registry() -> Result<Value, String> {
     // Check if we have a cached registry
     let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
     if let Some(ref registry) = *cache {
@@ -96,49 +109,32 @@ fn fetch_app_registry() -> Result<Value, String> {
         return Ok(registry.clone());
     }
 
-    // Fetch the registry from GitHub
-    let registry_url =
-        "https://raw.githubusercontent.com/fleuristes/app-registry/refs/heads/main/apps.json";
-    info!("Fetching app registry from {}", registry_url);
-    let response = get(registry_url).map_err(|e| {
-        error!("Failed to fetch app registry: {}", e);
-        format!("Failed to fetch app registry: {}", e)
-    })?;
-
-    let registry_json: Value = response.json().map_err(|e| {
-        error!("Failed to parse app registry JSON: {}", e);
-        format!("Failed to parse app registry JSON: {}", e)
-    })?;
+    let registry_json = load_app_registry()?;
 
     // Cache the registry
     *cache = Some(registry_json.clone());
-    info!("Successfully fetched and cached app registry");
     Ok(registry_json)
 }
 
-fn ensure_env_setup() -> Result<(), String> {
-    // Skip for test mode
-    if crate::environment::is_test_mode() {
-        return Ok(());
-    }
+// Loads the registry from every configured source, bypassing the memory cache
+pub(crate) fn load_app_registry() -> Result<Value, String> {
+    let (registry_json, statuses) = crate::registry::fetch_registry_sources(
+        &crate::registry::get_registry_sources(),
+        &crate::registry::get_registry_cache_dir(),
+    )?;
 
-    // Check if environment setup is already marked as complete
-    let mut setup_complete = ENV_SETUP_COMPLETE.lock().unwrap();
-    if *setup_complete {
-        debug!("Environment already set up");
-        return Ok(());
+    info!(
+        "Loaded app registry from {} sources",
+        statuses.iter().filter(|s| s.source.is_some()).count()
+    );
+    crate::registry::set_registry_status(statuses);
+    if let Err(e) = crate::search::index_registry(&registry_json) {
+        warn!("Failed to build search index: {}", e);
     }
-
-    // Ensure environment is set up synchronously
-    info!("Ensuring environment is set up before fetching app configs");
-    ensure_environment_sync()?;
-
-    // Mark setup as complete
-    *setup_complete = true;
-    Ok(())
+    Ok(registry_json)
 }
 
-fn replace_env_vars(input: &str, env: &serde_json::Value) -> String {
+pub(crate) fn replace_env_vars(input: &str, env: &serde_json::Value) -> String {
     let mut result = input.to_string();
 
     // Find all ${...} patterns
@@ -175,76 +171,344 @@ fn replace_env_vars(input: &str, env: &serde_json::Value) -> String {
     result
 }
 
-pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
-    debug!(
-        "Getting app configurations, test_mode: {}",
-        crate::environment::is_test_mode()
-    );
+// npx, uvx, bunx and deno apps run through a shim that exports the captured
+// shell environment, everything else gets it in its env
+fn runs_through_shim(config: &AppConfig) -> bool {
+    matches!(config.runtime.as_str(), "npx" | "uvx")
+        || crate::runtimes::managed_runtime(&config.runtime).is_some()
+}
+
+// Builds the client config entry for an app, substituting `${VAR}` placeholders
+// in its args from `env`
+
+fn build_server_entry(config: &AppConfig, env: &Value) -> Value {
+    // Captured variables are for the command itself, never passed into a container
+    let captured = crate::shell_env::captured_environment();
+    let mut app_env = env.as_object().cloned().unwrap_or_default();
+    crate::shell_env::remove_from(&mut app_env, &captured);
+    let env = Value::Object(app_env);
+
+    let args = match &config.container {
+        Some(container) => crate::container::build_container_args(container, &env, &config.args),
+        None => config.args.clone(),
+    };
+    let processed_args = args
+        .iter()
+        .map(|arg| replace_env_vars(arg, &env))
+        .collect::<Vec<String>>();
+
+    let mut env = env;
+    if !runs_through_shim(config) {
+        if let Some(env) = env.as_object_mut() {
+            crate::shell_env::apply_to(env, &captured);
+        }
+    }
+
+    json!({
+        "command": config.command,
+        "args": processed_args,
+        "env": env
+    })
+}
+
+// Maps a registry runtime to the command written into client configs. Anything
+// that isn't a known runtime is taken as the command itself.
+pub(crate) fn resolve_runtime_command(runtime: &str, npx_shim: &str, uvx_path: &str) -> String {
+    match runtime {
+        "npx" => npx_shim.to_string(),
+        "uvx" => uvx_path.to_string(),
+        runtime => match crate::runtimes::managed_runtime(runtime) {
+            Some(managed) => managed.shim_path().to_string_lossy().to_string(),
+            None => runtime.to_string(),
+        },
+    }
+}
+
+// Installs the managed runtime behind `runtime` if it has one, e.g. Bun for "bunx"
+pub(crate) fn ensure_managed_runtime(runtime: &str) -> Result<(), String> {
+    match crate::runtimes::managed_runtime(runtime) {
+        Some(managed) => crate::runtimes::ensure_runtime_shim(managed).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+// Installs the Node.js or Python an app's version range selected
+fn ensure_runtime_version(config: &AppConfig) -> Result<(), String> {
+    match (config.runtime.as_str(), &config.runtime_version) {
+        ("npx", Some(version)) => crate::environment::ensure_npx_shim_for(version).map(|_| ()),
+        ("uvx", Some(request)) => crate::environment::ensure_python(request),
+        _ => Ok(()),
+    }
+}
+
+// Absolute npx launchers like an nvm npx or one of Fleur's shims
+fn is_npx_launcher(command: &str) -> bool {
+    let path = Path::new(command);
+    path.is_absolute()
+        && path
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy().starts_with("npx"))
+}
+
+// Entries launched through an npx that is gone (an old nvm install, a moved
+// data dir) are pointed at Fleur's npx shim. Entries on a shim for another
+// Node.js keep it, the default one might not fit the app's range. Returns how
+// many were changed.
+pub fn repoint_npx_entries() -> Result<usize, String> {
+    let default_version = crate::environment::default_node_version();
+    let shim_path = crate::environment::get_npx_shim_path();
+    if check_shim(&shim_path) != ShimHealth::Healthy {
+        debug!("NPX shim isn't usable, leaving client entries alone");
+        return Ok(0);
+    }
+    let shim = shim_path.to_string_lossy().to_string();
 
+    let mut repointed = 0;
+    for client in ClientType::all() {
+        // Don't create configs for clients that never had one
+        if !get_config_path(&client)?.exists() {
+            continue;
+        }
+
+        let mut config = get_config(&client)?;
+        let mut changed = 0;
+        if let Some(servers) = config["mcpServers"].as_object_mut() {
+            for (key, entry) in servers.iter_mut() {
+                let Some(command) = entry["command"].as_str() else {
+                    continue;
+                };
+                if command == shim || !is_npx_launcher(command) {
+                    continue;
+                }
+                let path = Path::new(command);
+                let shim_version = crate::environment::npx_shim_version(path);
+                let broken = if shim_version.is_some() {
+                    check_shim(path) != ShimHealth::Healthy
+                } else {
+                    !path.exists()
+                };
+                if !broken {
+                    continue;
+                }
+                if let Some(version) = shim_version.filter(|version| *version != default_version) {
+                    error!(
+                        "{} for {} needs Node.js {} but its shim {} couldn't be repaired",
+                        key,
+                        client.as_str(),
+                        version,
+                        command
+                    );
+                    continue;
+                }
+                info!(
+                    "Pointing {} for {} from {} to {}",
+                    key,
+                    client.as_str(),
+                    command,
+                    shim
+                );
+                entry["command"] = Value::String(shim.clone());
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            save_config(&config, &client)?;
+            repointed += changed;
+        }
+    }
+    Ok(repointed)
+}
+
+// Swaps `previous` for `current` in the env of installed apps that don't run
+// through a shim. Returns how many entries changed.
+pub fn reapply_shell_environment(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Result<usize, String> {
+    let keys: HashSet<String> = get_app_configs()?
+        .into_iter()
+        .filter(|(_, config)| !runs_through_shim(config))
+        .map(|(_, config)| config.mcp_key)
+        .collect();
+
+    let mut updated = 0;
+    for client in ClientType::all() {
+        if !get_config_path(&client)?.exists() {
+            continue;
+        }
+
+        let mut config = get_config(&client)?;
+        let mut changed = 0;
+        if let Some(servers) = config["mcpServers"].as_object_mut() {
+            for (key, entry) in servers.iter_mut() {
+                let Some(entry) = entry.as_object_mut().filter(|_| keys.contains(key)) else {
+                    continue;
+                };
+                let mut env = entry
+                    .get("env")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                let before = env.clone();
+                crate::shell_env::remove_from(&mut env, previous);
+                crate::shell_env::apply_to(&mut env, current);
+                if env != before {
+                    entry.insert("env".to_string(), Value::Object(env));
+                    changed += 1;
+                }
+            }
+        }
+
+        if changed > 0 {
+            save_config(&config, &client)?;
+            updated += changed;
+        }
+    }
+    Ok(updated)
+}
+
+// Absolute paths of the npx and uvx shims that back the "npx" and "uvx" runtimes
+pub(crate) fn get_runtime_paths() -> Result<(String, String), String> {
     // Ensure environment is set up first (skip in test mode)
-    ensure_env_setup()?;
+    ensure_ready()?;
 
     // In test mode, use test paths directly
     let (npx_shim, uvx_path) = if crate::environment::is_test_mode() {
         debug!("Using test paths for npx_shim and uvx_path");
         (
             "/test/.local/share/fleur/bin/npx-fleur".to_string(),
-            "/test/.local/bin/uvx".to_string(),
+            "/test/.local/share/fleur/bin/uvx-fleur".to_string(),
         )
     } else {
         // Get absolute paths, and fail if they can't be obtained
         let npx_shim = ensure_npx_shim()?;
-        let uvx_path = get_uvx_path()?;
-        (npx_shim, uvx_path)
+        let uvx_shim = ensure_uvx_shim()?;
+        (npx_shim, uvx_shim)
     };
 
     info!("Using npx_shim: {}", npx_shim);
     info!("Using uvx_path: {}", uvx_path);
+    Ok((npx_shim, uvx_path))
+}
 
-    let registry = fetch_app_registry()?;
-    let apps = registry.as_array().ok_or_else(|| {
-        let err = "App registry is not an array".to_string();
-        error!("{}", err);
-        err
-    })?;
+pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
+    debug!(
+        "Getting app configurations, test_mode: {}",
+        crate::environment::is_test_mode()
+    );
+
+    let (npx_shim, uvx_path) = get_runtime_paths()?;
 
+    let mut apps = load_registry_apps()?;
+    crate::custom_apps::merge_with_registry(&mut apps);
     let mut configs = Vec::new();
+    let mut container_engine = None;
 
+    let platform = crate::constraints::current_platform();
+    let target = crate::binaries::current_target();
     for app in apps {
-        let name = app["name"]
-            .as_str()
-            .ok_or("App name is missing")?
-            .to_string();
-        let config = app["config"].as_object().ok_or("App config is missing")?;
-
-        let mcp_key = config["mcpKey"]
-            .as_str()
-            .ok_or("mcpKey is missing")?
-            .to_string();
-        let runtime = config["runtime"].as_str().ok_or("runtime is missing")?;
-
-        let command = match runtime {
-            "npx" => npx_shim.clone(),
-            "uvx" => uvx_path.clone(),
-            _ => runtime.to_string(),
-        };
+        let mut unavailable_reason = crate::constraints::check_app_constraints(&app.config).err();
 
-        let args_value = config["args"].as_array().ok_or("args is missing")?;
-        let args: Vec<String> = args_value
-            .iter()
-            .map(|arg| arg.as_str().unwrap_or("").to_string())
-            .collect();
+        let mut args = crate::constraints::resolve_args(&app.config, platform);
+        let mut runtime_version = None;
+        let download = match app.config.runtime.as_str() {
+            crate::binaries::BINARY_RUNTIME => app.config.downloads.get(&target).cloned(),
+            _ => None,
+        };
+        let command = match app.config.runtime.as_str() {
+            crate::container::CONTAINER_RUNTIME => container_engine
+                .get_or_insert_with(crate::container::detect_container_engine)
+                .clone()
+                .unwrap_or_else(|reason| {
+                    unavailable_reason.get_or_insert(reason);
+                    crate::container::CONTAINER_RUNTIME.to_string()
+                }),
+            crate::binaries::BINARY_RUNTIME => match &download {
+                Some(download) => {
+                    match crate::binaries::binary_path(&app.config.mcp_key, download) {
+                        Ok(path) => path.to_string_lossy().to_string(),
+                        Err(reason) => {
+                            unavailable_reason.get_or_insert(reason);
+                            crate::binaries::BINARY_RUNTIME.to_string()
+                        }
+                    }
+                }
+                None => {
+                    unavailable_reason
+                        .get_or_insert_with(|| format!("No prebuilt binary for {}", target));
+                    crate::binaries::BINARY_RUNTIME.to_string()
+                }
+            },
+            crate::git::GIT_RUNTIME => {
+                let entrypoint = app
+                    .config
+                    .git
+                    .as_ref()
+                    .ok_or_else(|| "No git source configured".to_string())
+                    .and_then(|source| {
+                        let checkout = crate::git::checkout_dir(&app.config.mcp_key)?;
+                        crate::git::resolve_entrypoint(source, &checkout, &args)
+                    });
+                match entrypoint {
+                    Ok((command, entrypoint_args)) => {
+                        args = entrypoint_args;
+                        command
+                    }
+                    Err(reason) => {
+                        unavailable_reason.get_or_insert(reason);
+                        crate::git::GIT_RUNTIME.to_string()
+                    }
+                }
+            }
+            "npx" if app.config.requires.node.is_some() => {
+                match crate::environment::node_version_for(app.config.requires.node.as_deref()) {
+                    Ok(version) => {
+                        let shim = crate::environment::get_npx_shim_path_for(&version);
+                        runtime_version = Some(version);
+                        shim.to_string_lossy().to_string()
+                    }
+                    Err(reason) => {
+                        unavailable_reason.get_or_insert(reason);
+                        npx_shim.clone()
+                    }
+                }
+            }
+            "uvx" if app.config.requires.python.is_some() => {
+                let range = app.config.requires.python.as_deref().unwrap_or_default();
+                match crate::constraints::python_request(range) {
+                    Ok(request) => {
+                        args.splice(0..0, ["--python".to_string(), request.clone()]);
+                        runtime_version = Some(request);
+                    }
+                    Err(reason) => {
+                        unavailable_reason.get_or_insert(reason);
+                    }
+                }
+                uvx_path.clone()
+            }
+            runtime => resolve_runtime_command(runtime, &npx_shim, &uvx_path),
+        };
+        if let Some(reason) = &unavailable_reason {
+            info!("App '{}' is unavailable: {}", app.name, reason);
+        }
 
         debug!(
             "Configured app '{}' with command: '{}', args: {:?}",
-            name, command, args
+            app.name, command, args
         );
         configs.push((
-            name,
+            app.name,
             AppConfig {
-                mcp_key,
+                mcp_key: app.config.mcp_key,
+                runtime: app.config.runtime,
                 command,
                 args,
+                unavailable_reason,
+                container: app.config.container,
+                download,
+                git: app.config.git,
+                runtime_version,
             },
         ));
     }
@@ -350,8 +614,8 @@ pub fn preload_dependencies() -> Result<(), String> {
 
         #[cfg(target_os = "windows")]
         {
-            // Use the npm executable from the NVM installation if available
-            if let Ok((_, npx_path)) = crate::environment::get_nvm_node_paths() {
+            // Use the npm executable from Fleur's Node.js installation if available
+            if let Ok((_, npx_path)) = crate::environment::get_node_paths() {
                 let npm_path = std::path::Path::new(&npx_path)
                     .parent()
                     .map(|p| p.join("npm.cmd"))
@@ -396,8 +660,9 @@ pub fn preload_dependencies() -> Result<(), String> {
     Ok(())
 }
 
-#[tauri::command]
-pub fn install(
+// Installs can clone and build, pull images or download binaries, so the
+// command runs this off the main thread
+pub fn install_app(
     app_name: &str,
     env_vars: Option<serde_json::Value>,
     client: &str,
@@ -408,12 +673,18 @@ pub fn install(
         crate::environment::is_test_mode()
     );
 
-    ensure_env_setup()?;
+    ensure_ready()?;
+    crate::environment::ensure_healthy_shims();
 
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
     let configs = get_app_configs()?;
     if let Some((_, config)) = configs.iter().find(|(name, _)| name == app_name) {
+        if let Some(reason) = &config.unavailable_reason {
+            warn!("Refusing to install {}: {}", app_name, reason);
+            return Err(format!("{} is not available: {}", app_name, reason));
+        }
+
         let mut config_json = get_config(&client_type)?;
         let mcp_key = config.mcp_key.clone();
         let command = config.command.clone();
@@ -424,6 +695,16 @@ pub fn install(
             app_name, command, args
         );
 
+        // Downloaded before the path check since the download creates the path
+        ensure_managed_runtime(&config.runtime)?;
+        ensure_runtime_version(config)?;
+        if let Some(download) = &config.download {
+            crate::binaries::ensure_binary(std::path::Path::new(&command), download)?;
+        }
+        if let Some(source) = &config.git {
+            crate::git::ensure_checkout(&mcp_key, source)?;
+        }
+
         // Skip path validation entirely in test mode
         if !crate::environment::is_test_mode() {
             if !std::path::Path::new(&command).exists() {
@@ -440,6 +721,10 @@ pub fn install(
             debug!("Test mode: skipping path validation for {}", command);
         }
 
+        if let Some(container) = &config.container {
+            crate::container::ensure_image(&command, &container.image)?;
+        }
+
         if let Some(mcp_servers) = config_json
             .get_mut("mcpServers")
             .and_then(|v| v.as_object_mut())
@@ -469,17 +754,7 @@ pub fn install(
                 existing_env
             };
 
-            // Process args to replace environment variables
-            let processed_args = args
-                .iter()
-                .map(|arg| replace_env_vars(arg, &env))
-                .collect::<Vec<String>>();
-
-            let app_config = json!({
-                "command": command,
-                "args": processed_args,
-                "env": env
-            });
+            let app_config = build_server_entry(config, &env);
 
             debug!("Adding config for {}: {:?}", mcp_key, app_config);
             mcp_servers.insert(mcp_key.clone(), app_config);
@@ -521,6 +796,17 @@ pub fn install(
     }
 }
 
+#[tauri::command]
+pub async fn install(
+    app_name: String,
+    env_vars: Option<serde_json::Value>,
+    client: String,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || install_app(&app_name, env_vars, &client))
+        .await
+        .map_err(|e| format!("Install failed unexpectedly: {}", e))?
+}
+
 #[tauri::command]
 pub fn uninstall(app_name: &str, client: &str) -> Result<String, String> {
     info!("Uninstalling app: {} for client: {:?}", app_name, client);
@@ -599,7 +885,7 @@ pub fn save_app_env(
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
 
-    ensure_env_setup()?;
+    ensure_ready()?;
 
     let configs = get_app_configs()?;
     if let Some((_, config)) = configs.iter().find(|(name, _)| name == app_name) {
@@ -675,6 +961,176 @@ pub fn get_app_env(app_name: &str, client: &str) -> Result<Value, String> {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutdatedApp {
+    pub app_name: String,
+    pub mcp_key: String,
+    pub installed_command: String,
+    pub expected_command: String,
+    pub installed_args: Vec<String>,
+    pub expected_args: Vec<String>,
+    pub added_args: Vec<String>,
+    pub removed_args: Vec<String>,
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn diff_installed_app(
+    app_name: &str,
+    config: &AppConfig,
+    server_config: &Value,
+) -> Option<OutdatedApp> {
+    let env = server_config
+        .get("env")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let expected = build_server_entry(config, &env);
+
+    let installed_command = server_config["command"].as_str().unwrap_or("").to_string();
+    let expected_command = config.command.clone();
+    let installed_args = string_list(server_config.get("args"));
+    let expected_args = string_list(expected.get("args"));
+
+    if installed_command == expected_command && installed_args == expected_args {
+        return None;
+    }
+
+    let added_args = expected_args
+        .iter()
+        .filter(|arg| !installed_args.contains(arg))
+        .cloned()
+        .collect();
+    let removed_args = installed_args
+        .iter()
+        .filter(|arg| !expected_args.contains(arg))
+        .cloned()
+        .collect();
+
+    Some(OutdatedApp {
+        app_name: app_name.to_string(),
+        mcp_key: config.mcp_key.clone(),
+        installed_command,
+        expected_command,
+        installed_args,
+        expected_args,
+        added_args,
+        removed_args,
+    })
+}
+
+#[tauri::command]
+pub fn get_outdated_apps(client: &str) -> Result<Vec<OutdatedApp>, String> {
+    debug!("Checking for outdated apps for client: {}", client);
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let configs = get_app_configs()?;
+    let config_json = get_config(&client_type)?;
+    let mcp_servers = config_json
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .ok_or("Failed to find mcpServers in config")?;
+
+    let outdated: Vec<OutdatedApp> = configs
+        .iter()
+        .filter_map(|(name, config)| {
+            mcp_servers
+                .get(&config.mcp_key)
+                .and_then(|server_config| diff_installed_app(name, config, server_config))
+        })
+        .collect();
+
+    info!(
+        "Found {} outdated apps for client {}",
+        outdated.len(),
+        client_type.as_str()
+    );
+    Ok(outdated)
+}
+
+pub fn update_installed_app(app_name: &str, client: &str) -> Result<String, String> {
+    info!("Updating app: {} for client: {}", app_name, client);
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    ensure_ready()?;
+    crate::environment::ensure_healthy_shims();
+
+    let configs = get_app_configs()?;
+    let (_, config) = configs
+        .iter()
+        .find(|(name, _)| name == app_name)
+        .ok_or_else(|| format!("No configuration available for '{}'", app_name))?;
+    if let Some(reason) = &config.unavailable_reason {
+        return Err(format!("{} is not available: {}", app_name, reason));
+    }
+    if let Some(container) = &config.container {
+        crate::container::ensure_image(&config.command, &container.image)?;
+    }
+    ensure_managed_runtime(&config.runtime)?;
+    ensure_runtime_version(config)?;
+    if let Some(download) = &config.download {
+        crate::binaries::ensure_binary(std::path::Path::new(&config.command), download)?;
+    }
+    // Updating a git app fetches the ref again and rebuilds if it moved
+    if let Some(source) = &config.git {
+        crate::git::ensure_checkout(&config.mcp_key, source)?;
+    }
+
+    let mut config_json = get_config(&client_type)?;
+    let server_config = config_json
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Failed to find mcpServers in config")?
+        .get_mut(&config.mcp_key)
+        .and_then(|v| v.as_object_mut())
+        .ok_or_else(|| format!("App '{}' is not installed", app_name))?;
+
+    // Only the command, args and captured shell variables come from Fleur,
+    // everything else the user set on the entry is kept
+    let env = server_config
+        .get("env")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let updated = build_server_entry(config, &env);
+    server_config.insert("command".to_string(), updated["command"].clone());
+    server_config.insert("args".to_string(), updated["args"].clone());
+    server_config.insert("env".to_string(), updated["env"].clone());
+
+    save_config(&config_json, &client_type)?;
+    info!(
+        "Successfully updated app: {} for client: {}",
+        app_name, client
+    );
+    Ok(format!(
+        "Updated {} configuration for {}",
+        config.mcp_key, app_name
+    ))
+}
+
+#[tauri::command]
+pub async fn update_app(app_name: String, client: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || update_installed_app(&app_name, &client))
+        .await
+        .map_err(|e| format!("Update failed unexpectedly: {}", e))?
+}
+
 #[tauri::command]
 pub fn get_app_statuses(client: &str) -> Result<Value, String> {
     debug!(
@@ -686,11 +1142,12 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
 
-    ensure_env_setup()?;
+    ensure_ready()?;
 
     let config_json = get_config(&client_type)?;
     let mut installed_apps = json!({});
     let mut configured_apps = json!({});
+    let mut unavailable_apps = json!({});
 
     let app_configs = match get_app_configs() {
         Ok(configs) => configs,
@@ -699,7 +1156,8 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
             error!("Failed to get app configs: {}. Returning empty status.", e);
             return Ok(json!({
                 "installed": {},
-                "configured": {}
+                "configured": {},
+                "unavailable": {}
             }));
         }
     };
@@ -708,6 +1166,9 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
         for (app_name, config) in app_configs {
             installed_apps[&app_name] = json!(mcp_servers.contains_key(&config.mcp_key));
             configured_apps[&app_name] = json!(!config.command.is_empty());
+            if let Some(reason) = config.unavailable_reason {
+                unavailable_apps[&app_name] = json!(reason);
+            }
         }
     }
 
@@ -719,14 +1180,21 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
     );
     Ok(json!({
         "installed": installed_apps,
-        "configured": configured_apps
+        "configured": configured_apps,
+        "unavailable": unavailable_apps
     }))
 }
 
+fn registry_apps_json() -> Result<Value, String> {
+    let mut apps = load_registry_apps()?;
+    crate::custom_apps::merge_with_registry(&mut apps);
+    serde_json::to_value(apps).map_err(|e| format!("Failed to serialize app registry: {}", e))
+}
+
 #[tauri::command]
 pub fn get_app_registry() -> Result<Value, String> {
     info!("Fetching app registry...");
-    let result = fetch_app_registry();
+    let result = registry_apps_json();
     match &result {
         Ok(value) => info!("Successfully fetched app registry: {}", value),
         Err(e) => error!("Failed to fetch app registry: {}", e),
@@ -746,7 +1214,7 @@ pub fn refresh_app_registry() -> Result<Value, String> {
     }
 
     // Fetch fresh registry
-    let result = fetch_app_registry();
+    let result = registry_apps_json();
     match &result {
         Ok(value) => info!("Successfully refreshed app registry: {}", value),
         Err(e) => error!("Failed to refresh app registry: {}", e),
@@ -763,7 +1231,7 @@ pub fn install_fleur_mcp(client: &str) -> Result<String, String> {
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
 
     let mut config_json = get_config(&client_type)?;
-    let uvx_path = get_uvx_path()?;
+    let uvx_path = ensure_uvx_shim()?;
 
     if let Some(mcp_servers) = config_json
         .get_mut("mcpServers")
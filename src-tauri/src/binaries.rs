@@ -0,0 +1,171 @@
+use crate::registry::BinaryDownload;
+use log::{debug, info, warn};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const BINARY_RUNTIME: &str = "binary";
+const BINARIES_DIR: &str = "binaries";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Key of the registry download that fits this machine, e.g. "macos-aarch64"
+pub fn current_target() -> String {
+    format!(
+        "{}-{}",
+        crate::constraints::current_platform(),
+        std::env::consts::ARCH
+    )
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn is_valid_sha256(checksum: &str) -> bool {
+    checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// A single path component that can't climb out of the directory it's joined to
+pub fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':', '\0'])
+}
+
+// Where a download is kept. The path only depends on the registry entry so
+// every client points at the same file and a new release gets a new path.
+pub fn binary_path(mcp_key: &str, download: &BinaryDownload) -> Result<PathBuf, String> {
+    if !is_plain_file_name(mcp_key) {
+        return Err(format!("Invalid binary name '{}'", mcp_key));
+    }
+    if !is_valid_sha256(&download.sha256) {
+        return Err(format!("Invalid sha256 for {}", download.url));
+    }
+    let file_name = download
+        .url
+        .split(['?', '#'])
+        .next()
+        .and_then(|url| url.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(mcp_key);
+    if !is_plain_file_name(file_name) {
+        return Err(format!("Invalid binary file name '{}'", file_name));
+    }
+
+    Ok(crate::environment::get_fleur_data_dir()
+        .join(BINARIES_DIR)
+        .join(mcp_key)
+        .join(download.sha256[..12].to_lowercase())
+        .join(file_name))
+}
+
+fn file_matches(path: &Path, sha256: &str) -> bool {
+    fs::read(path)
+        .map(|contents| sha256_hex(&contents).eq_ignore_ascii_case(sha256))
+        .unwrap_or(false)
+}
+
+pub(crate) fn download_file(url: &str) -> Result<Vec<u8>, String> {
+    download_file_with_progress(url, |_, _| {})
+}
+
+// Like download_file, reporting (downloaded, total) bytes as chunks arrive
+pub(crate) fn download_file_with_progress(
+    url: &str,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    info!("Downloading {}", url);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to download {}: HTTP {}",
+            url,
+            response.status()
+        ));
+    }
+
+    let total = response.content_length();
+    let mut contents = Vec::with_capacity(total.unwrap_or(0) as usize);
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        let read = response
+            .read(&mut chunk)
+            .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+        if read == 0 {
+            break;
+        }
+        contents.extend_from_slice(&chunk[..read]);
+        on_progress(contents.len() as u64, total);
+    }
+    Ok(contents)
+}
+
+#[cfg(unix)]
+pub(crate) fn mark_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn mark_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+// Downloads the binary to `path` unless a copy with the right checksum is already there
+pub fn ensure_binary(path: &Path, download: &BinaryDownload) -> Result<(), String> {
+    if path.is_file() {
+        if file_matches(path, &download.sha256) {
+            debug!("Binary {} is up to date", path.display());
+            return Ok(());
+        }
+        warn!(
+            "Binary {} doesn't match its checksum, downloading it again",
+            path.display()
+        );
+    }
+
+    let contents = download_file(&download.url)?;
+
+    let checksum = sha256_hex(&contents);
+    if !checksum.eq_ignore_ascii_case(&download.sha256) {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            download.url,
+            download.sha256.to_lowercase(),
+            checksum
+        ));
+    }
+
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("Invalid binary path {}", path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+
+    // Written next to the target first so a failed write never leaves a
+    // half-downloaded binary where clients would run it
+    let partial_path = parent.join(format!(
+        "{}.partial",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::write(&partial_path, &contents)
+        .map_err(|e| format!("Failed to write {}: {}", partial_path.display(), e))?;
+    mark_executable(&partial_path)?;
+    fs::rename(&partial_path, path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    info!("Installed {} to {}", download.url, path.display());
+    Ok(())
+}
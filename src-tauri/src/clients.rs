@@ -49,6 +49,15 @@ impl ClientType {
     pub fn default() -> ClientType {
         ClientType::Claude
     }
+
+    // Hard cap on the number of MCP tools the client will expose to the model
+    pub fn tool_limit(&self) -> Option<usize> {
+        match self {
+            ClientType::Claude => None,
+            ClientType::Cursor => Some(40),
+            ClientType::Windsurf => Some(100),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
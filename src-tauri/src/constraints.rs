@@ -0,0 +1,247 @@
+use crate::registry::RegistryAppConfig;
+use lazy_static::lazy_static;
+use log::debug;
+use semver::{Op, Version, VersionReq};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+pub const PLATFORMS: [&str; 3] = ["macos", "windows", "linux"];
+
+lazy_static! {
+    static ref PYTHON_VERSION: Mutex<Option<Option<Version>>> = Mutex::new(None);
+    static ref COMPARATOR_GAP: regex::Regex = regex::Regex::new(r"([\d*])\s+([<>=~^])").unwrap();
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeVersions {
+    pub node: Option<Version>,
+    pub python: Option<Version>,
+}
+
+pub fn current_platform() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macos"
+    } else if cfg!(target_os = "windows") {
+        "windows"
+    } else {
+        "linux"
+    }
+}
+
+// Accepts loose versions such as "v20.9.0", "3.12" or "Python 3.11.4"
+pub fn parse_version(text: &str) -> Option<Version> {
+    let token = text
+        .split_whitespace()
+        .map(|t| t.trim_start_matches('v'))
+        .find(|t| t.starts_with(|c: char| c.is_ascii_digit()))?;
+
+    let core: String = token
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let mut parts: Vec<&str> = core.split('.').filter(|p| !p.is_empty()).collect();
+    parts.truncate(3);
+    while parts.len() < 3 {
+        parts.push("0");
+    }
+    Version::parse(&parts.join(".")).ok()
+}
+
+// Also takes npm style ranges that separate comparators with spaces, ">=20 <23"
+pub fn parse_version_req(range: &str) -> Result<VersionReq, String> {
+    let normalized = COMPARATOR_GAP.replace_all(range.trim(), "$1, $2");
+    VersionReq::parse(&normalized).map_err(|e| format!("Invalid version range '{}': {}", range, e))
+}
+
+// Rewrites a semver range as the PEP 440 request `uvx --python` expects, e.g.
+// "^3.10" becomes ">=3.10,<4"
+pub fn python_request(range: &str) -> Result<String, String> {
+    let requirement = parse_version_req(range)?;
+    let mut specifiers = Vec::new();
+    for comparator in &requirement.comparators {
+        let parts: Vec<u64> = [Some(comparator.major), comparator.minor, comparator.patch]
+            .into_iter()
+            .flatten()
+            .collect();
+        let join = |parts: &[u64]| {
+            parts
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(".")
+        };
+        let version = join(&parts);
+        // The exclusive upper bound of "~" and "^" ranges
+        let bump = |index: usize| {
+            let mut upper = parts[..=index].to_vec();
+            upper[index] += 1;
+            join(&upper)
+        };
+
+        match comparator.op {
+            Op::Exact | Op::Wildcard if parts.len() < 3 => {
+                specifiers.push(format!("=={}.*", version))
+            }
+            Op::Exact | Op::Wildcard => specifiers.push(format!("=={}", version)),
+            Op::Greater => specifiers.push(format!(">{}", version)),
+            Op::GreaterEq => specifiers.push(format!(">={}", version)),
+            Op::Less => specifiers.push(format!("<{}", version)),
+            Op::LessEq => specifiers.push(format!("<={}", version)),
+            Op::Tilde => {
+                specifiers.push(format!(">={}", version));
+                specifiers.push(format!("<{}", bump(if parts.len() > 1 { 1 } else { 0 })));
+            }
+            Op::Caret => {
+                specifiers.push(format!(">={}", version));
+                let index = parts
+                    .iter()
+                    .position(|part| *part != 0)
+                    .unwrap_or(parts.len() - 1);
+                specifiers.push(format!("<{}", bump(index)));
+            }
+            _ => return Err(format!("Unsupported Python version range '{}'", range)),
+        }
+    }
+    Ok(specifiers.join(","))
+}
+
+pub fn find_binary(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+
+    #[cfg(target_os = "windows")]
+    let extensions: Vec<String> = std::env::var("PATHEXT")
+        .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+        .split(';')
+        .map(|ext| ext.to_lowercase())
+        .collect();
+
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+
+        #[cfg(target_os = "windows")]
+        for ext in &extensions {
+            let candidate = dir.join(format!("{}{}", name, ext));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+
+        None
+    })
+}
+
+fn detect_python_version() -> Option<Version> {
+    if crate::environment::is_test_mode() {
+        return None;
+    }
+
+    let mut cached = PYTHON_VERSION.lock().unwrap();
+    if let Some(version) = cached.as_ref() {
+        return version.clone();
+    }
+
+    let version = ["python3", "python"].iter().find_map(|program| {
+        let output = crate::environment::create_windowless_command(program)
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        // Older Pythons print their version on stderr
+        let text = format!(
+            "{} {}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        parse_version(&text)
+    });
+
+    debug!("Detected Python version: {:?}", version);
+    *cached = Some(version.clone());
+    version
+}
+
+pub fn resolve_args(config: &RegistryAppConfig, platform: &str) -> Vec<String> {
+    config
+        .platform_args
+        .get(platform)
+        .cloned()
+        .unwrap_or_else(|| config.args.clone())
+}
+
+fn check_version(
+    name: &str,
+    range: Option<&str>,
+    installed: Option<&Version>,
+) -> Result<(), String> {
+    let range = match range {
+        Some(range) => range,
+        None => return Ok(()),
+    };
+
+    let requirement = parse_version_req(range)?;
+    match installed {
+        Some(version) if requirement.matches(version) => Ok(()),
+        Some(version) => Err(format!(
+            "Requires {} {} but {} is installed",
+            name, range, version
+        )),
+        None => Err(format!("Requires {} {} but it was not found", name, range)),
+    }
+}
+
+// Returns the reason the app can't run on `platform`, if any
+pub fn check_constraints(
+    config: &RegistryAppConfig,
+    platform: &str,
+    versions: &RuntimeVersions,
+    binary_exists: impl Fn(&str) -> bool,
+) -> Result<(), String> {
+    if !config.platforms.is_empty() && !config.platforms.iter().any(|p| p == platform) {
+        return Err(format!("Only available on {}", config.platforms.join(", ")));
+    }
+
+    let requires = &config.requires;
+    check_version("Node.js", requires.node.as_deref(), versions.node.as_ref())?;
+    // uvx downloads a matching Python itself
+    if config.runtime != "uvx" {
+        check_version(
+            "Python",
+            requires.python.as_deref(),
+            versions.python.as_ref(),
+        )?;
+    }
+
+    let missing: Vec<&str> = requires
+        .binaries
+        .iter()
+        .filter(|binary| !binary_exists(binary))
+        .map(|binary| binary.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Requires {} on the PATH", missing.join(", ")));
+    }
+
+    Ok(())
+}
+
+pub fn check_app_constraints(config: &RegistryAppConfig) -> Result<(), String> {
+    // Node.js is whichever pinned release matches the app's range. Python is
+    // only probed for apps that ask for it since it means spawning a process.
+    let versions = RuntimeVersions {
+        node: crate::environment::node_version_for(config.requires.node.as_deref())
+            .ok()
+            .and_then(|version| parse_version(&version)),
+        python: config
+            .requires
+            .python
+            .as_ref()
+            .filter(|_| config.runtime != "uvx")
+            .and_then(|_| detect_python_version()),
+    };
+    check_constraints(config, current_platform(), &versions, |binary| {
+        find_binary(binary).is_some()
+    })
+}
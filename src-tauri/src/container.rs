@@ -0,0 +1,111 @@
+use crate::registry::ContainerSpec;
+use log::{debug, info};
+use serde_json::Value;
+use std::path::PathBuf;
+
+pub const CONTAINER_RUNTIME: &str = "docker";
+const CONTAINER_ENGINES: [&str; 2] = ["docker", "podman"];
+
+// Install locations that aren't on the PATH of apps launched from the Dock or Start menu
+fn known_engine_paths(engine: &str) -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        let mut paths = vec![
+            PathBuf::from("/usr/local/bin").join(engine),
+            PathBuf::from("/opt/homebrew/bin").join(engine),
+        ];
+        match engine {
+            "docker" => paths.push(PathBuf::from(
+                "/Applications/Docker.app/Contents/Resources/bin/docker",
+            )),
+            _ => paths.push(PathBuf::from("/opt/podman/bin/podman")),
+        }
+        paths
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let program_files = std::env::var("ProgramFiles")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("C:\\Program Files"));
+        match engine {
+            "docker" => vec![program_files.join("Docker\\Docker\\resources\\bin\\docker.exe")],
+            _ => vec![program_files.join("RedHat\\Podman\\podman.exe")],
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        vec![PathBuf::from("/usr/bin").join(engine)]
+    }
+}
+
+// Path of the container engine to run containerized servers with, Docker
+// is preferred over Podman when both are installed
+pub fn detect_container_engine() -> Result<String, String> {
+    if crate::environment::is_test_mode() {
+        return Ok("/test/bin/docker".to_string());
+    }
+
+    for engine in CONTAINER_ENGINES {
+        let found = crate::constraints::find_binary(engine)
+            .or_else(|| known_engine_paths(engine).into_iter().find(|p| p.is_file()));
+        if let Some(path) = found {
+            debug!("Using container engine at {}", path.display());
+            return Ok(path.to_string_lossy().to_string());
+        }
+    }
+
+    Err("Requires Docker or Podman, but neither was found".to_string())
+}
+
+// Builds `run -i --rm` args that pass the entry's env through to the container
+pub fn build_container_args(spec: &ContainerSpec, env: &Value, args: &[String]) -> Vec<String> {
+    let mut run_args = vec!["run".to_string(), "-i".to_string(), "--rm".to_string()];
+
+    if let Some(env) = env.as_object() {
+        for key in env.keys() {
+            run_args.push("-e".to_string());
+            run_args.push(key.clone());
+        }
+    }
+
+    for volume in &spec.volumes {
+        run_args.push("-v".to_string());
+        run_args.push(volume.clone());
+    }
+
+    run_args.push(spec.image.clone());
+    run_args.extend(args.iter().cloned());
+    run_args
+}
+
+// Makes sure the image is available locally so the first client launch
+// doesn't stall on a pull
+pub fn ensure_image(engine: &str, image: &str) -> Result<(), String> {
+    if crate::environment::is_test_mode() {
+        return Ok(());
+    }
+
+    let present = crate::environment::create_windowless_command(engine)
+        .args(["image", "inspect", image])
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", engine, e))?;
+    if present.status.success() {
+        debug!("Image {} is already available", image);
+        return Ok(());
+    }
+
+    info!("Pulling image {}", image);
+    let pulled = crate::environment::create_windowless_command(engine)
+        .args(["pull", image])
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", engine, e))?;
+    if !pulled.status.success() {
+        let stderr = String::from_utf8_lossy(&pulled.stderr);
+        return Err(format!("Failed to pull image {}: {}", image, stderr.trim()));
+    }
+
+    info!("Pulled image {}", image);
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use crate::registry::{GitSource, RegistryApp, RegistryAppConfig};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+pub const CUSTOM_SOURCE: &str = "custom";
+const CUSTOM_APPS_FILE: &str = "custom_apps.json";
+const CUSTOM_RUNTIMES: [&str; 4] = ["npx", "uvx", "binary", "git"];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomApp {
+    pub name: String,
+    pub mcp_key: String,
+    // One of "npx", "uvx", "binary" or "git"
+    pub runtime: String,
+    // An npm or PyPI package spec, the path to the executable for binaries or
+    // the repository URL for git
+    pub package: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitSource>,
+}
+
+impl CustomApp {
+    // Args as they appear in the client config, with the package in front
+    pub fn resolved_args(&self) -> Vec<String> {
+        let mut args = match self.runtime.as_str() {
+            "npx" => vec!["-y".to_string(), self.package.clone()],
+            "uvx" => vec![self.package.clone()],
+            _ => Vec::new(),
+        };
+        args.extend(self.args.iter().cloned());
+        args
+    }
+
+    pub fn to_registry_app(&self) -> RegistryApp {
+        // Binaries don't go through a shim, their path is the command itself
+        let runtime = match self.runtime.as_str() {
+            "binary" => self.package.clone(),
+            runtime => runtime.to_string(),
+        };
+
+        RegistryApp {
+            name: self.name.clone(),
+            description: format!("Custom {} server: {}", self.runtime, self.package),
+            icon: None,
+            category: "Custom".to_string(),
+            price: "Free".to_string(),
+            developer: String::new(),
+            source_url: None,
+            features: Vec::new(),
+            setup: Vec::new(),
+            env_vars: Vec::new(),
+            config: RegistryAppConfig {
+                mcp_key: self.mcp_key.clone(),
+                runtime,
+                args: self.resolved_args(),
+                platforms: Vec::new(),
+                requires: Default::default(),
+                platform_args: Default::default(),
+                container: None,
+                downloads: Default::default(),
+                git: self.git.clone(),
+            },
+            source: Some(CUSTOM_SOURCE.to_string()),
+            signer: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+fn get_custom_apps_path() -> PathBuf {
+    crate::environment::get_fleur_data_dir().join(CUSTOM_APPS_FILE)
+}
+
+pub fn load_custom_apps() -> Result<Vec<CustomApp>, String> {
+    let path = get_custom_apps_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn save_custom_apps(apps: &[CustomApp]) -> Result<(), String> {
+    let path = get_custom_apps_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(apps)
+        .map_err(|e| format!("Failed to serialize custom apps: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// Derives a config key from the app name, e.g. "My Server" becomes "my-server"
+pub fn mcp_key_for(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Registry apps win over custom apps that were created before the registry
+// gained an app with the same name
+pub fn merge_with_registry(registry_apps: &mut Vec<RegistryApp>) {
+    let custom_apps = match load_custom_apps() {
+        Ok(apps) => apps,
+        Err(e) => {
+            error!("Failed to load custom apps: {}", e);
+            return;
+        }
+    };
+
+    for custom in custom_apps {
+        if registry_apps.iter().any(|app| app.name == custom.name) {
+            warn!(
+                "Custom app '{}' is shadowed by a registry app with the same name",
+                custom.name
+            );
+            continue;
+        }
+        registry_apps.push(custom.to_registry_app());
+    }
+}
+
+fn validate_custom_app(app: &CustomApp) -> Result<(), String> {
+    if app.name.trim().is_empty() {
+        return Err("Custom app name cannot be empty".to_string());
+    }
+    if app.mcp_key.is_empty() {
+        return Err(format!("'{}' is not a usable app name", app.name));
+    }
+    if !CUSTOM_RUNTIMES.contains(&app.runtime.as_str()) {
+        return Err(format!(
+            "Unsupported runtime '{}', expected one of {}",
+            app.runtime,
+            CUSTOM_RUNTIMES.join(", ")
+        ));
+    }
+    if app.package.trim().is_empty() {
+        return Err("A package or path is required".to_string());
+    }
+    if app.runtime == crate::git::GIT_RUNTIME {
+        let git = app
+            .git
+            .as_ref()
+            .ok_or("A ref, build steps and entrypoint are required for git")?;
+        crate::git::validate_source(git)?;
+    }
+
+    if app.runtime == "binary" && !crate::environment::is_test_mode() {
+        let path = std::path::Path::new(&app.package);
+        if !path.is_absolute() || !path.is_file() {
+            return Err(format!(
+                "Binary path '{}' must be an absolute path to an existing file",
+                app.package
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Client entries Fleur didn't write are never replaced. A saved custom app
+// with the same key means the entry is one Fleur wrote earlier.
+fn ensure_key_is_free(custom: &CustomApp, saved: &[CustomApp], client: &str) -> Result<(), String> {
+    if saved.iter().any(|app| app.mcp_key == custom.mcp_key) {
+        return Ok(());
+    }
+    // Unknown clients are reported by the install itself
+    let Some(client_type) = crate::clients::ClientType::from_str(client) else {
+        return Ok(());
+    };
+
+    let config = crate::app::get_config(&client_type)?;
+    if config
+        .get("mcpServers")
+        .and_then(|servers| servers.get(&custom.mcp_key))
+        .is_some()
+    {
+        return Err(format!(
+            "{} already has a '{}' server that wasn't added by Fleur",
+            client, custom.mcp_key
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_custom_apps() -> Result<Vec<CustomApp>, String> {
+    load_custom_apps()
+}
+
+#[tauri::command]
+pub fn install_custom_app(
+    name: &str,
+    runtime: &str,
+    package: &str,
+    args: Option<Vec<String>>,
+    env_vars: Option<Value>,
+    client: &str,
+    git: Option<GitSource>,
+) -> Result<String, String> {
+    // The repository URL doubles as the package of git apps
+    let git = git.map(|source| GitSource {
+        url: package.trim().to_string(),
+        ..source
+    });
+    let custom = CustomApp {
+        name: name.trim().to_string(),
+        mcp_key: mcp_key_for(name),
+        runtime: runtime.to_string(),
+        package: package.trim().to_string(),
+        args: args.unwrap_or_default(),
+        git,
+    };
+    validate_custom_app(&custom)?;
+
+    let registry_apps = crate::registry::load_registry_apps()?;
+    if registry_apps.iter().any(|app| app.name == custom.name) {
+        return Err(format!(
+            "'{}' is already in the registry, install it from there",
+            custom.name
+        ));
+    }
+    if let Some(app) = registry_apps
+        .iter()
+        .find(|app| app.config.mcp_key == custom.mcp_key)
+    {
+        return Err(format!(
+            "'{}' would use the same config key as '{}'",
+            custom.name, app.name
+        ));
+    }
+
+    let mut apps = load_custom_apps()?;
+    if let Some(other) = apps
+        .iter()
+        .find(|app| app.mcp_key == custom.mcp_key && app.name != custom.name)
+    {
+        return Err(format!(
+            "'{}' would use the same config key as '{}'",
+            custom.name, other.name
+        ));
+    }
+    ensure_key_is_free(&custom, &apps, client)?;
+
+    // Installs look the app up from the saved list, so it's saved first and
+    // the previous list is restored when the install fails
+    let previous = apps.clone();
+    apps.retain(|app| app.name != custom.name);
+    apps.push(custom.clone());
+    save_custom_apps(&apps)?;
+
+    let result = crate::app::install_app(&custom.name, env_vars, client);
+    match &result {
+        Ok(_) => info!(
+            "Saved custom app '{}' ({} {})",
+            custom.name, custom.runtime, custom.package
+        ),
+        Err(e) => {
+            warn!("Installing custom app '{}' failed: {}", custom.name, e);
+            if let Err(e) = save_custom_apps(&previous) {
+                warn!("Failed to roll back custom app '{}': {}", custom.name, e);
+            }
+        }
+    }
+    result
+}
+
+#[tauri::command]
+pub fn remove_custom_app(name: &str, client: &str) -> Result<String, String> {
+    let mut apps = load_custom_apps()?;
+    if !apps.iter().any(|app| app.name == name) {
+        return Err(format!("No custom app named '{}'", name));
+    }
+
+    crate::app::uninstall(name, client)?;
+    apps.retain(|app| app.name != name);
+    save_custom_apps(&apps)?;
+    info!("Removed custom app '{}'", name);
+    Ok(format!("Removed custom app {}", name))
+}
@@ -0,0 +1,467 @@
+use crate::clients::{self, ClientType};
+use crate::environment::{check_shim, parse_shim_targets, ShimHealth, ShimTarget};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org/";
+const PYPI_URL: &str = "https://pypi.org/simple/";
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Something the UI can offer to run when a check fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FixAction {
+    // Runs (or retries) the uv and Node.js setup
+    SetupEnvironment,
+    RegenerateNpxShim,
+    RegenerateUvxShim,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    pub id: String,
+    pub label: String,
+    pub ok: bool,
+    pub detail: String,
+    pub remediation: Option<String>,
+    pub fix: Option<FixAction>,
+}
+
+impl DiagnosticCheck {
+    fn pass(id: &str, label: &str, detail: String) -> Self {
+        DiagnosticCheck {
+            id: id.to_string(),
+            label: label.to_string(),
+            ok: true,
+            detail,
+            remediation: None,
+            fix: None,
+        }
+    }
+
+    fn fail(
+        id: &str,
+        label: &str,
+        detail: String,
+        remediation: String,
+        fix: Option<FixAction>,
+    ) -> Self {
+        DiagnosticCheck {
+            id: id.to_string(),
+            label: label.to_string(),
+            ok: false,
+            detail,
+            remediation: Some(remediation),
+            fix,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolInfo {
+    pub name: String,
+    pub path: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShimInfo {
+    pub path: String,
+    pub contents: Option<String>,
+    pub targets: Vec<ShimTarget>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientConfigInfo {
+    pub client: String,
+    pub installed: bool,
+    pub path: Option<String>,
+    pub exists: bool,
+    pub writable: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentReport {
+    pub tools: Vec<ToolInfo>,
+    pub npx_shim: ShimInfo,
+    pub uvx_shim: ShimInfo,
+    pub path: Vec<String>,
+    pub client_configs: Vec<ClientConfigInfo>,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+fn tool_version(path: &str) -> Option<String> {
+    let output = crate::environment::create_windowless_command(path)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+// A file can be written if it opens for appending, a missing one if the
+// closest existing directory above it accepts new files
+pub fn is_writable(path: &Path) -> bool {
+    if path.exists() {
+        return fs::OpenOptions::new().append(true).open(path).is_ok();
+    }
+
+    let Some(dir) = path.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+        return false;
+    };
+    let probe = dir.join(".fleur-write-check");
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+// Any HTTP response counts, only failing to connect means unreachable
+pub fn check_reachable(id: &str, label: &str, url: &str) -> DiagnosticCheck {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(REACHABILITY_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(url).send());
+
+    match response {
+        Ok(response) if !response.status().is_server_error() => {
+            DiagnosticCheck::pass(id, label, format!("{} answered {}", url, response.status()))
+        }
+        Ok(response) => DiagnosticCheck::fail(
+            id,
+            label,
+            format!("{} answered {}", url, response.status()),
+            "The service is having problems, try again in a few minutes".to_string(),
+            None,
+        ),
+        Err(e) => DiagnosticCheck::fail(
+            id,
+            label,
+            format!("Could not reach {}: {}", url, e),
+            "Check your internet connection, VPN or proxy settings".to_string(),
+            None,
+        ),
+    }
+}
+
+// The remote endpoints installs depend on as (id, label, url)
+pub fn reachability_endpoints() -> Vec<(String, String, String)> {
+    let mut endpoints: Vec<(String, String, String)> = crate::registry::get_registry_sources()
+        .into_iter()
+        .filter(|source| source.location.starts_with("http"))
+        .map(|source| {
+            (
+                format!("registry:{}", source.name),
+                format!("Registry {}", source.name),
+                source.location,
+            )
+        })
+        .collect();
+    endpoints.push(("npm".into(), "npm registry".into(), NPM_REGISTRY_URL.into()));
+    endpoints.push(("pypi".into(), "PyPI".into(), PYPI_URL.into()));
+    endpoints
+}
+
+fn diagnose_node(tools: &mut Vec<ToolInfo>, checks: &mut Vec<DiagnosticCheck>) -> bool {
+    let node_version = crate::environment::default_node_version();
+    match crate::environment::get_node_paths() {
+        Ok((node_path, npx_path)) => {
+            let version = tool_version(&node_path);
+            checks.push(match &version {
+                Some(version) => DiagnosticCheck::pass(
+                    "node",
+                    "Node.js",
+                    format!("{} at {}", version, node_path),
+                ),
+                None => DiagnosticCheck::fail(
+                    "node",
+                    "Node.js",
+                    format!("{} doesn't run", node_path),
+                    format!(
+                        "Reinstall Node.js {} by setting up the environment",
+                        node_version
+                    ),
+                    Some(FixAction::SetupEnvironment),
+                ),
+            });
+            tools.push(ToolInfo {
+                name: "node".to_string(),
+                path: Some(node_path),
+                version: version.clone(),
+            });
+            tools.push(ToolInfo {
+                name: "npx".to_string(),
+                version: tool_version(&npx_path),
+                path: Some(npx_path),
+            });
+            version.is_some()
+        }
+        Err(e) => {
+            checks.push(DiagnosticCheck::fail(
+                "node",
+                "Node.js",
+                e,
+                format!(
+                    "Install Node.js {} by setting up the environment",
+                    node_version
+                ),
+                Some(FixAction::SetupEnvironment),
+            ));
+            for name in ["node", "npx"] {
+                tools.push(ToolInfo {
+                    name: name.to_string(),
+                    path: None,
+                    version: None,
+                });
+            }
+            false
+        }
+    }
+}
+
+fn diagnose_shim(
+    (id, label): (&str, &str),
+    runtime: &str,
+    shim_path: &Path,
+    fix: FixAction,
+    checks: &mut Vec<DiagnosticCheck>,
+) -> ShimInfo {
+    let contents = fs::read_to_string(shim_path).ok();
+    let targets = contents
+        .as_deref()
+        .map(parse_shim_targets)
+        .unwrap_or_default();
+
+    let fail = |detail: String, remediation: String| {
+        DiagnosticCheck::fail(id, label, detail, remediation, Some(fix))
+    };
+    checks.push(match check_shim(shim_path) {
+        ShimHealth::Healthy => {
+            DiagnosticCheck::pass(id, label, format!("{} is valid", shim_path.display()))
+        }
+        ShimHealth::Missing => fail(
+            format!("{} doesn't exist", shim_path.display()),
+            format!("Recreate the {}", label),
+        ),
+        ShimHealth::Stale(missing) => fail(
+            format!("Points at missing files: {}", missing.join(", ")),
+            format!(
+                "The shim points at a {} that is gone, regenerate it",
+                runtime
+            ),
+        ),
+        ShimHealth::OutdatedFormat(_) => fail(
+            "Was written by an older Fleur".to_string(),
+            "Regenerate the shim in the current format".to_string(),
+        ),
+    });
+
+    ShimInfo {
+        path: shim_path.to_string_lossy().to_string(),
+        contents,
+        targets,
+    }
+}
+
+// Apps run uvx through Fleur's shim, so that's the uv being checked
+fn diagnose_uv(tools: &mut Vec<ToolInfo>, checks: &mut Vec<DiagnosticCheck>) -> ShimInfo {
+    let uvx_shim = diagnose_shim(
+        ("uvxShim", "uvx shim"),
+        "uv",
+        &crate::environment::get_uvx_shim_path(),
+        FixAction::RegenerateUvxShim,
+        checks,
+    );
+    let uvx_path = uvx_shim
+        .targets
+        .iter()
+        .find(|target| target.name == "UVX")
+        .map(|target| target.path.clone());
+    let uv_path = uvx_path
+        .as_ref()
+        .map(|uvx| {
+            Path::new(uvx)
+                .with_file_name(crate::runtimes::executable_name("uv"))
+                .to_string_lossy()
+                .to_string()
+        })
+        .filter(|uv| Path::new(uv).exists());
+
+    let uvx_version = uvx_path.as_deref().and_then(tool_version);
+    checks.push(match (&uvx_path, &uvx_version) {
+        (Some(path), Some(version)) => {
+            DiagnosticCheck::pass("uvx", "uvx", format!("{} at {}", version, path))
+        }
+        (Some(path), None) => DiagnosticCheck::fail(
+            "uvx",
+            "uvx",
+            format!("{} doesn't run", path),
+            "Reinstall uv by setting up the environment".to_string(),
+            Some(FixAction::SetupEnvironment),
+        ),
+        (None, _) => DiagnosticCheck::fail(
+            "uvx",
+            "uvx",
+            "The uvx shim doesn't point at a uvx".to_string(),
+            "Install uv by setting up the environment".to_string(),
+            Some(FixAction::SetupEnvironment),
+        ),
+    });
+
+    tools.push(ToolInfo {
+        name: "uv".to_string(),
+        version: uv_path.as_deref().and_then(tool_version),
+        path: uv_path,
+    });
+    tools.push(ToolInfo {
+        name: "uvx".to_string(),
+        path: uvx_path,
+        version: uvx_version,
+    });
+    uvx_shim
+}
+
+fn diagnose_npx_shim(node_ok: bool, checks: &mut Vec<DiagnosticCheck>) -> ShimInfo {
+    // Rewriting the shim only helps once Node.js itself is in place
+    let fix = if node_ok {
+        FixAction::RegenerateNpxShim
+    } else {
+        FixAction::SetupEnvironment
+    };
+    diagnose_shim(
+        ("npxShim", "npx shim"),
+        "Node.js",
+        &crate::environment::get_npx_shim_path(),
+        fix,
+        checks,
+    )
+}
+
+fn diagnose_client_configs(checks: &mut Vec<DiagnosticCheck>) -> Vec<ClientConfigInfo> {
+    ClientType::all()
+        .iter()
+        .map(|client| {
+            let installed = clients::check_client_installed(client).unwrap_or(false);
+            let path = crate::app::get_config_path(client).ok();
+            let exists = path.as_ref().is_some_and(|path| path.exists());
+            let writable = path.as_deref().is_some_and(is_writable);
+
+            // Clients that aren't installed don't need a config yet
+            if installed {
+                let id = format!("clientConfig:{}", client.as_str().to_lowercase());
+                let label = format!("{} config", client.as_str());
+                let shown = path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                checks.push(if writable {
+                    DiagnosticCheck::pass(&id, &label, format!("{} is writable", shown))
+                } else {
+                    DiagnosticCheck::fail(
+                        &id,
+                        &label,
+                        format!("{} can't be written", shown),
+                        "Check the file's permissions or point Fleur at another config path"
+                            .to_string(),
+                        None,
+                    )
+                });
+            }
+
+            ClientConfigInfo {
+                client: client.as_str().to_string(),
+                installed,
+                path: path.map(|path| path.to_string_lossy().to_string()),
+                exists,
+                writable,
+            }
+        })
+        .collect()
+}
+
+pub fn diagnose(endpoints: &[(String, String, String)]) -> EnvironmentReport {
+    let mut checks = Vec::new();
+    let mut tools = Vec::new();
+
+    let node_ok = diagnose_node(&mut tools, &mut checks);
+    let uvx_shim = diagnose_uv(&mut tools, &mut checks);
+    let npx_shim = diagnose_npx_shim(node_ok, &mut checks);
+    let path = std::env::var_os("PATH")
+        .map(|path| {
+            std::env::split_paths(&path)
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for (id, label, url) in endpoints {
+        checks.push(check_reachable(id, label, url));
+    }
+    let client_configs = diagnose_client_configs(&mut checks);
+
+    let failed = checks.iter().filter(|check| !check.ok).count();
+    info!(
+        "Environment diagnosis finished with {} of {} checks failing",
+        failed,
+        checks.len()
+    );
+    EnvironmentReport {
+        tools,
+        npx_shim,
+        uvx_shim,
+        path,
+        client_configs,
+        checks,
+    }
+}
+
+fn fix_issue(action: FixAction) -> Result<String, String> {
+    debug!("Running environment fix {:?}", action);
+    match action {
+        FixAction::SetupEnvironment => {
+            crate::environment::rerun_setup().map(|_| "uv and Node.js are ready".to_string())
+        }
+        FixAction::RegenerateNpxShim => {
+            let path = crate::environment::ensure_npx_shim()?;
+            crate::app::repoint_npx_entries()?;
+            Ok(format!("Regenerated the npx shim at {}", path))
+        }
+        FixAction::RegenerateUvxShim => {
+            let path = crate::environment::ensure_uvx_shim()?;
+            Ok(format!("Regenerated the uvx shim at {}", path))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn diagnose_environment() -> Result<EnvironmentReport, String> {
+    tauri::async_runtime::spawn_blocking(|| diagnose(&reachability_endpoints()))
+        .await
+        .map_err(|e| format!("Environment diagnosis failed unexpectedly: {}", e))
+}
+
+#[tauri::command]
+pub async fn fix_environment_issue(action: FixAction) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || fix_issue(action))
+        .await
+        .map_err(|e| format!("Environment fix failed unexpectedly: {}", e))?
+}
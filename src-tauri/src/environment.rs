@@ -1,8 +1,11 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
 use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use tauri::Emitter;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -11,16 +14,68 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 pub const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-static UV_INSTALLED: AtomicBool = AtomicBool::new(false);
-static NVM_INSTALLED: AtomicBool = AtomicBool::new(false);
-static NODE_INSTALLED: AtomicBool = AtomicBool::new(false);
-static ENVIRONMENT_SETUP_STARTED: AtomicBool = AtomicBool::new(false);
-static ENVIRONMENT_SETUP_COMPLETED: AtomicBool = AtomicBool::new(false);
-static NODE_VERSION: &str = "v20.9.0";
+pub(crate) static NODE_VERSION: &str = "v20.9.0";
+// Node.js releases accepted for apps without a range of their own. The minimum
+// can be raised through the `nodeVersionRange` setting without a new build.
+const DEFAULT_NODE_VERSION_RANGE: &str = ">=20, <23";
+static UV_VERSION: &str = "0.5.11";
+// Python uvx apps get unless they ask for another through `requires.python`
+const DEFAULT_PYTHON_VERSION: &str = "3.12";
 static IS_TEST_MODE: AtomicBool = AtomicBool::new(false);
 
-// Lock to prevent concurrent environment setup operations
-static ENVIRONMENT_SETUP_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static TEST_DATA_DIR: Lazy<Mutex<Option<std::path::PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+pub const ENVIRONMENT_PROGRESS_EVENT: &str = "environment-progress";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupStage {
+    Checking,
+    Downloading,
+    Verifying,
+    Extracting,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupProgress {
+    pub stage: SetupStage,
+    pub message: String,
+    // e.g. "node 20.9.0", unset for stages covering the whole setup
+    pub runtime: Option<String>,
+    pub downloaded_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+}
+
+impl SetupProgress {
+    pub fn new(stage: SetupStage, message: impl Into<String>) -> Self {
+        SetupProgress {
+            stage,
+            message: message.into(),
+            runtime: None,
+            downloaded_bytes: None,
+            total_bytes: None,
+        }
+    }
+
+    pub fn for_runtime(mut self, runtime: &str) -> Self {
+        self.runtime = Some(runtime.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentStatus {
+    pub state: EnvironmentState,
+    pub last_progress: Option<SetupProgress>,
+}
+
+// Where progress events go, set once the Tauri app is up
+static PROGRESS_HANDLE: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+static LAST_PROGRESS: Lazy<Mutex<Option<SetupProgress>>> = Lazy::new(|| Mutex::new(None));
 
 #[cfg(feature = "test-utils")]
 pub fn set_test_mode(enabled: bool) {
@@ -31,9 +86,61 @@ pub fn is_test_mode() -> bool {
     IS_TEST_MODE.load(Ordering::SeqCst)
 }
 
+pub fn set_test_data_dir(path: Option<std::path::PathBuf>) {
+    let mut test_dir = TEST_DATA_DIR.lock().unwrap();
+    *test_dir = path;
+}
+
+pub fn set_progress_handle(handle: tauri::AppHandle) {
+    *PROGRESS_HANDLE.lock().unwrap() = Some(handle);
+}
+
+// Remembers the latest stage for get_environment_status and emits it to the UI.
+// Runtime installs for single apps once setup finished aren't setup progress.
+pub fn report_progress(progress: SetupProgress) {
+    if !ENVIRONMENT.record_progress(&progress) {
+        debug!(
+            "Not reporting {:?} outside environment setup: {}",
+            progress.stage, progress.message
+        );
+        return;
+    }
+    debug!(
+        "Environment setup {:?}: {}",
+        progress.stage, progress.message
+    );
+    if let Some(handle) = PROGRESS_HANDLE.lock().unwrap().as_ref() {
+        if let Err(e) = handle.emit(ENVIRONMENT_PROGRESS_EVENT, &progress) {
+            error!("Failed to emit {} event: {}", ENVIRONMENT_PROGRESS_EVENT, e);
+        }
+    }
+    *LAST_PROGRESS.lock().unwrap() = Some(progress);
+}
+
+// Root directory for everything Fleur manages on disk (shims, caches, runtimes)
+pub fn get_fleur_data_dir() -> std::path::PathBuf {
+    if let Some(dir) = TEST_DATA_DIR.lock().unwrap().clone() {
+        return dir;
+    }
+
+    if is_test_mode() {
+        return std::path::PathBuf::from("/test/.local/share/fleur");
+    }
+
+    #[cfg(target_os = "macos")]
+    let data_dir = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/fleur");
+
+    #[cfg(target_os = "windows")]
+    let data_dir = dirs::data_local_dir().unwrap_or_default().join("fleur");
+
+    data_dir
+}
+
 pub fn get_npx_shim_path() -> std::path::PathBuf {
     if is_test_mode() {
-        return std::path::PathBuf::from("/test/.local/share/fleur/bin/npx-fleur");
+        return get_fleur_data_dir().join("bin").join("npx-fleur");
     }
 
     #[cfg(target_os = "macos")]
@@ -78,11 +185,79 @@ pub fn get_npx_shim_path() -> std::path::PathBuf {
     }
 }
 
-fn find_existing_uvx() -> Option<String> {
+// Executables inside the Node.js archive Fleur installs
+fn managed_node_executables() -> (std::path::PathBuf, std::path::PathBuf) {
+    if cfg!(target_os = "windows") {
+        ("node.exe".into(), "npx.cmd".into())
+    } else {
+        ("bin/node".into(), "bin/npx".into())
+    }
+}
+
+pub fn node_version_range() -> semver::VersionReq {
+    if let Some(range) =
+        crate::settings::get_setting("nodeVersionRange").and_then(|v| v.as_str().map(String::from))
+    {
+        match crate::constraints::parse_version_req(&range) {
+            Ok(requirement) => return requirement,
+            Err(e) => warn!("Ignoring invalid nodeVersionRange setting: {}", e),
+        }
+    }
+    crate::constraints::parse_version_req(DEFAULT_NODE_VERSION_RANGE)
+        .expect("default Node.js range is valid")
+}
+
+// A Node.js already installed by Fleur that fits the range is kept, otherwise
+// the pinned release that fits it is used
+pub fn default_node_version() -> String {
+    let range = node_version_range();
+    let installed = crate::runtimes::installed_versions("node")
+        .into_iter()
+        .filter(|version| range.matches(version))
+        .max();
+    if let Some(version) = installed {
+        return version.to_string();
+    }
+
+    let fallback = NODE_VERSION.trim_start_matches('v');
+    crate::runtimes::select_version("node", Some(&range.to_string()), fallback).unwrap_or_else(
+        |e| {
+            warn!("{}, falling back to Node.js {}", e, fallback);
+            fallback.to_string()
+        },
+    )
+}
+
+// The Node.js release for an app's semver range, the default one without a range
+pub fn node_version_for(range: Option<&str>) -> Result<String, String> {
+    crate::runtimes::select_version("node", range, &default_node_version())
+}
+
+// Fleur owns its Node.js so nvm, fnm, volta or the user's shell setup don't
+// matter. Each version lives in its own directory next to the others.
+pub fn get_node_dir_for(version: &str) -> std::path::PathBuf {
+    crate::runtimes::runtime_dir("node", version)
+}
+
+pub fn get_node_dir() -> std::path::PathBuf {
+    get_node_dir_for(&default_node_version())
+}
+
+fn managed_uvx_path() -> std::path::PathBuf {
+    crate::runtimes::runtime_dir("uv", UV_VERSION).join(crate::runtimes::executable_name("uvx"))
+}
+
+pub(crate) fn find_existing_uvx() -> Option<String> {
     if is_test_mode() {
         return Some("/test/.local/bin/uvx".to_string());
     }
 
+    let managed_uvx = managed_uvx_path();
+    if managed_uvx.is_file() {
+        debug!("Found Fleur's uvx at {}", managed_uvx.display());
+        return Some(managed_uvx.to_string_lossy().to_string());
+    }
+
     #[cfg(target_os = "macos")]
     {
         let home_dir = match dirs::home_dir() {
@@ -231,596 +406,496 @@ pub fn get_uvx_path() -> Result<String, String> {
     Err("uvx not found in PATH and installation failed. Please install it manually.".to_string())
 }
 
-pub fn get_nvm_node_paths() -> Result<(String, String), String> {
-    debug!("get_nvm_node_paths called, test_mode: {}", is_test_mode());
-
-    if is_test_mode() {
-        debug!("Using test mode paths for nvm/node");
-        return Ok((
-            "/test/.nvm/versions/node/v20.9.0/bin/node".to_string(),
-            "/test/.nvm/versions/node/v20.9.0/bin/npx".to_string(),
-        ));
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-        export NVM_DIR="$HOME/.nvm"
-        [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-        nvm use {} > /dev/null 2>&1
-        which node
-        which npx
-    "#,
-            NODE_VERSION
-        );
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to get node paths: {}", e))?;
-
-        if !output.status.success() {
-            return Err("Failed to get node and npx paths".to_string());
-        }
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut lines = output_str.lines();
-
-        let node_path = lines
-            .next()
-            .ok_or("Failed to get node path")?
-            .trim()
-            .to_string();
-
-        let npx_path = lines
-            .next()
-            .ok_or("Failed to get npx path")?
-            .trim()
-            .to_string();
+pub fn get_node_paths() -> Result<(String, String), String> {
+    get_node_paths_for(&default_node_version())
+}
 
-        // Only validate paths in non-test mode
-        if !is_test_mode() && !node_path.contains(".nvm/versions/node") {
-            debug!("Node path validation failed: {}", node_path);
-            return Err("Node path is not from nvm installation".to_string());
-        }
+pub fn get_node_paths_for(version: &str) -> Result<(String, String), String> {
+    let node_dir = get_node_dir_for(version);
+    let (node, npx) = managed_node_executables();
+    let node_path = node_dir.join(node);
 
-        Ok((node_path, npx_path))
+    if !is_test_mode() && !node_path.is_file() {
+        return Err(format!(
+            "Node.js {} is not installed in {}",
+            version,
+            node_dir.display()
+        ));
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        if !check_nvm_installed() {
-            return Err("NVM for Windows is not installed".to_string());
-        }
-
-        let nvm_root = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-            .ok_or("Could not determine NVM_HOME")?;
-
-        let version_no_v = NODE_VERSION.trim_start_matches('v');
-
-        let _ = Command::new("nvm")
-            .arg("use")
-            .arg(version_no_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
+    Ok((
+        node_path.to_string_lossy().to_string(),
+        node_dir.join(npx).to_string_lossy().to_string(),
+    ))
+}
 
-        let possible_node_paths = vec![
-            nvm_root.join(version_no_v).join("node.exe"),
-            nvm_root.join(format!("v{}", version_no_v)).join("node.exe"),
-        ];
+// Bumped whenever the shim layout changes so older shims get rewritten
+const SHIM_FORMAT: u32 = 1;
+const SHIM_FORMAT_MARKER: &str = "fleur-shim-format:";
 
-        let node_path = possible_node_paths
-            .iter()
-            .find(|path| path.exists())
-            .ok_or_else(|| {
-                format!(
-                    "Node.js executable not found at any of the expected paths: {:?}",
-                    possible_node_paths
-                )
-            })?;
-
-        let parent_dir = node_path
-            .parent()
-            .ok_or("Could not determine parent directory of node.exe")?;
+// `NODE="..."` in the sh shims, `set NODE=...` in the Windows ones
+static SHIM_TARGET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^(?:set )?(NODE|NPX|UVX)="?([^"\r\n]+)"?\r?$"#).unwrap());
 
-        let npx_path = parent_dir.join("npx.cmd");
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShimTarget {
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+}
 
-        if !npx_path.exists() {
-            return Err(format!(
-                "NPX executable not found at expected path: {}",
-                npx_path.display()
-            ));
-        }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShimHealth {
+    Healthy,
+    Missing,
+    // Points at a Node.js, npx or uvx that is gone
+    Stale(Vec<String>),
+    // Written by another Fleur release, `None` when it has no format header
+    OutdatedFormat(Option<u32>),
+}
 
-        Ok((
-            node_path.to_string_lossy().to_string(),
-            npx_path.to_string_lossy().to_string(),
-        ))
-    }
+// The executables baked into an npx or uvx shim
+pub fn parse_shim_targets(contents: &str) -> Vec<ShimTarget> {
+    SHIM_TARGET
+        .captures_iter(contents)
+        .map(|captures| {
+            let path = captures[2].trim().to_string();
+            ShimTarget {
+                name: captures[1].to_string(),
+                exists: std::path::Path::new(&path).exists(),
+                path,
+            }
+        })
+        .collect()
 }
 
-pub fn ensure_npx_shim() -> Result<String, String> {
-    if is_test_mode() {
-        debug!("Using test mode path for npx shim");
-        return Ok("/test/.local/share/fleur/bin/npx-fleur".to_string());
-    }
+pub fn shim_format(contents: &str) -> Option<u32> {
+    contents
+        .lines()
+        .find_map(|line| line.split_once(SHIM_FORMAT_MARKER))
+        .and_then(|(_, format)| format.trim().parse().ok())
+}
 
-    let shim_path = get_npx_shim_path();
+pub fn check_shim(shim_path: &std::path::Path) -> ShimHealth {
+    let Ok(contents) = std::fs::read_to_string(shim_path) else {
+        return ShimHealth::Missing;
+    };
 
-    // Only create the shim if it doesn't exist
-    if shim_path.exists() {
-        debug!("NPX shim already exists at {}", shim_path.display());
-        return Ok(shim_path.to_string_lossy().to_string());
+    let targets = parse_shim_targets(&contents);
+    let missing: Vec<String> = targets
+        .iter()
+        .filter(|target| !target.exists)
+        .map(|target| target.path.clone())
+        .collect();
+    if !missing.is_empty() {
+        return ShimHealth::Stale(missing);
     }
 
-    info!("Creating NPX shim...");
+    match shim_format(&contents) {
+        Some(SHIM_FORMAT) if !targets.is_empty() => ShimHealth::Healthy,
+        format => ShimHealth::OutdatedFormat(format),
+    }
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        let (node_path, npx_path) = get_nvm_node_paths()?;
+fn npx_shim_script(node_path: &str, npx_path: &str) -> String {
+    if cfg!(target_os = "windows") {
+        let node_dir = std::path::Path::new(node_path)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default();
+        format!(
+            r#"@echo off
+:: NPX shim for Fleur on Windows
+:: {marker} {format}
 
-        if let Some(parent) = shim_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create shim directory: {}", e))?;
-        }
+set NODE_PATH={}
+set NODE={}
+set NPX={}
+set PATH=%NODE_PATH%;%PATH%
 
-        let shim_content = format!(
+"%NPX%" %*
+"#,
+            node_dir,
+            node_path,
+            npx_path,
+            marker = SHIM_FORMAT_MARKER,
+            format = SHIM_FORMAT
+        )
+    } else {
+        // A PATH captured from the user's login shell goes before the inherited one
+        let captured_path = crate::shell_env::captured_path()
+            .map(|path| format!("{}:", crate::shell_env::shell_quote(&path)))
+            .unwrap_or_default();
+        format!(
             r#"#!/bin/sh
 # NPX shim for Fleur
+# {marker} {format}
 
 NODE="{}"
 NPX="{}"
 
-export PATH="$(dirname "$NODE"):$PATH"
+{env}export PATH="$(dirname "$NODE")":{captured_path}"$PATH"
 
 exec "$NPX" "$@"
 "#,
-            node_path, npx_path
-        );
-
-        std::fs::write(&shim_path, shim_content)
-            .map_err(|e| format!("Failed to write shim script: {}", e))?;
-
-        Command::new("chmod")
-            .arg("+x")
-            .arg(&shim_path)
-            .output()
-            .map_err(|e| format!("Failed to make shim executable: {}", e))?;
+            node_path,
+            npx_path,
+            marker = SHIM_FORMAT_MARKER,
+            format = SHIM_FORMAT,
+            env = crate::shell_env::shim_exports(),
+            captured_path = captured_path
+        )
     }
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        match get_nvm_node_paths() {
-            Ok((node_path, npx_path)) => {
-                if let Some(parent) = shim_path.parent() {
-                    std::fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create shim directory: {}", e))?;
-                }
-
-                let node_dir = std::path::Path::new(&node_path)
-                    .parent()
-                    .ok_or("Could not determine parent directory of node.exe")?
-                    .to_string_lossy();
-
-                let shim_content = format!(
-                    r#"@echo off
-:: NPX shim for Fleur on Windows
+// Apps that need another Node.js than the default get a shim of their own
+pub fn get_npx_shim_path_for(version: &str) -> std::path::PathBuf {
+    if version == default_node_version() {
+        return get_npx_shim_path();
+    }
 
-set NODE_PATH={}
-set NODE={}
-set NPX={}
-set PATH=%NODE_PATH%;%PATH%
+    let shim_name = if cfg!(target_os = "windows") {
+        format!("npx-fleur-{}.cmd", version)
+    } else {
+        format!("npx-fleur-{}", version)
+    };
+    get_fleur_data_dir().join("bin").join(shim_name)
+}
 
-"%NPX%" %*
-"#,
-                    node_dir, node_path, npx_path
-                );
+fn write_npx_shim(shim_path: &std::path::Path, version: &str) -> Result<String, String> {
+    let (node_path, npx_path) = get_node_paths_for(version)?;
+    let script = npx_shim_script(&node_path, &npx_path);
 
-                std::fs::write(&shim_path, shim_content)
-                    .map_err(|e| format!("Failed to write shim script: {}", e))?;
+    // Shims written for an older Node.js or by nvm based versions get replaced
+    if std::fs::read_to_string(shim_path).ok().as_deref() == Some(script.as_str()) {
+        debug!("NPX shim already exists at {}", shim_path.display());
+        return Ok(shim_path.to_string_lossy().to_string());
+    }
 
-                info!("NPX shim created at {}", shim_path.display());
-            }
-            Err(e) => {
-                error!("Failed to get node paths for shim creation: {}", e);
-                return Err(format!("Failed to create NPX shim: {}", e));
-            }
-        }
+    if let Some(parent) = shim_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create shim directory: {}", e))?;
     }
+    std::fs::write(shim_path, script).map_err(|e| format!("Failed to write shim script: {}", e))?;
+    crate::binaries::mark_executable(shim_path)?;
 
     info!("NPX shim created at {}", shim_path.display());
     Ok(shim_path.to_string_lossy().to_string())
 }
 
-fn check_node_version() -> Result<String, String> {
+pub fn ensure_npx_shim() -> Result<String, String> {
     if is_test_mode() {
-        return Ok(NODE_VERSION.to_string());
+        debug!("Using test mode path for npx shim");
+        return Ok("/test/.local/share/fleur/bin/npx-fleur".to_string());
     }
 
-    // If we already confirmed node is installed with correct version, return early
-    if NODE_INSTALLED.load(Ordering::SeqCst) {
-        debug!("Node.js already confirmed as installed");
-        return Ok(NODE_VERSION.to_string());
+    write_npx_shim(&get_npx_shim_path(), &default_node_version())
+}
+
+// The Node.js version a shim in Fleur's bin directory was written for
+pub(crate) fn npx_shim_version(shim_path: &std::path::Path) -> Option<String> {
+    // Not file_stem, the version's dots would be taken for an extension
+    let file_name = shim_path.file_name()?.to_string_lossy().to_string();
+    let name = file_name.strip_suffix(".cmd").unwrap_or(&file_name);
+    if name == "npx-fleur" {
+        return Some(default_node_version());
     }
+    name.strip_prefix("npx-fleur-")
+        .map(|version| version.to_string())
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-          export NVM_DIR="$HOME/.nvm"
-          [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-          nvm list | grep -w "{}" || true
-      "#,
-            NODE_VERSION
+// The default npx shim followed by the versioned ones in Fleur's bin directory
+fn npx_shims() -> Vec<std::path::PathBuf> {
+    let default_shim = get_npx_shim_path();
+    let mut shims = vec![default_shim.clone()];
+    if let Ok(entries) = std::fs::read_dir(get_fleur_data_dir().join("bin")) {
+        shims.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path != &default_shim && npx_shim_version(path).is_some()),
         );
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to check nvm node version: {}", e))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        if output_str.contains(NODE_VERSION) {
-            info!("Node.js {} is already installed via nvm", NODE_VERSION);
-            NODE_INSTALLED.store(true, Ordering::SeqCst);
-            return Ok(NODE_VERSION.to_string());
-        }
     }
+    shims
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        if check_nvm_installed() {
-            let nvm_cmd = Command::new("nvm")
-                .arg("list")
-                .creation_flags(CREATE_NO_WINDOW)
-                .output()
-                .map_err(|e| format!("Failed to check nvm node version: {}", e))?;
-
-            let output_str = String::from_utf8_lossy(&nvm_cmd.stdout);
-            let version_no_v = NODE_VERSION.trim_start_matches('v');
-
-            if output_str.contains(NODE_VERSION) || output_str.contains(version_no_v) {
-                info!("Node.js {} is already installed via nvm", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::SeqCst);
-                return Ok(NODE_VERSION.to_string());
-            }
-
-            let nvm_root = std::env::var("NVM_HOME")
-                .ok()
-                .map(std::path::PathBuf::from)
-                .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-                .ok_or("Could not determine NVM_HOME")?;
-
-            let node_exists = nvm_root.join(version_no_v).join("node.exe").exists()
-                || nvm_root
-                    .join(format!("v{}", version_no_v))
-                    .join("node.exe")
-                    .exists();
-
-            if node_exists {
-                info!("Node.js {} binary found via nvm", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::SeqCst);
-                return Ok(NODE_VERSION.to_string());
-            }
+// Rewrites every npx shim that is missing targets or has an older format,
+// reinstalling its Node.js when that is what went missing
+pub fn repair_npx_shims() -> Vec<std::path::PathBuf> {
+    let default_shim = get_npx_shim_path();
+    let mut repaired = Vec::new();
+    for shim in npx_shims() {
+        let health = check_shim(&shim);
+        // A missing default shim is the setup's job, it's created on first run
+        if health == ShimHealth::Healthy || (health == ShimHealth::Missing && shim == default_shim)
+        {
+            continue;
         }
-    }
-
-    #[cfg(target_os = "macos")]
-    let version_command = Command::new("node")
-        .arg("--version")
-        .output()
-        .map_err(|e| format!("Failed to check node version: {}", e))?;
-
-    #[cfg(target_os = "windows")]
-    let version_command = Command::new("node")
-        .arg("--version")
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| format!("Failed to check node version: {}", e))?;
-
-    if version_command.status.success() {
-        let version = String::from_utf8_lossy(&version_command.stdout)
-            .trim()
-            .to_string();
+        let Some(version) = npx_shim_version(&shim) else {
+            continue;
+        };
 
-        if version == NODE_VERSION {
-            info!("Node.js {} is already installed system-wide", NODE_VERSION);
-            NODE_INSTALLED.store(true, Ordering::SeqCst);
-            return Ok(version);
+        warn!("NPX shim {} needs repair: {:?}", shim.display(), health);
+        let result = if is_test_mode() {
+            Ok(())
+        } else {
+            install_node_version(&version)
+        };
+        match result.and_then(|_| write_npx_shim(&shim, &version)) {
+            Ok(_) => repaired.push(shim),
+            Err(e) => error!("Failed to repair NPX shim {}: {}", shim.display(), e),
         }
-
-        info!("Found Node.js {} but {} is required", version, NODE_VERSION);
-        return Ok(version);
     }
-
-    Err("Node.js not found".to_string())
+    repaired
 }
 
-fn check_nvm_version() -> Result<String, String> {
-    if is_test_mode() {
-        return Ok("0.40.1".to_string());
+// Run on startup and before installs since setup itself only runs once
+pub fn ensure_healthy_shims() {
+    let repaired = repair_npx_shims();
+    if !repaired.is_empty() {
+        info!("Repaired {} NPX shim(s)", repaired.len());
     }
-
-    let shell_command = r#"
-        export NVM_DIR="$HOME/.nvm"
-        [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-        nvm --version
-    "#;
-
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg(shell_command)
-        .output()
-        .map_err(|e| format!("Failed to check nvm version: {}", e))?;
-
-    if !output.status.success() {
-        return Err("Failed to get nvm version".to_string());
+    let uvx_health = check_shim(&get_uvx_shim_path());
+    if !matches!(uvx_health, ShimHealth::Healthy | ShimHealth::Missing) {
+        warn!("UVX shim needs repair: {:?}", uvx_health);
+        if let Err(e) = ensure_uvx_shim() {
+            error!("Failed to repair UVX shim: {}", e);
+        }
+    }
+    match crate::app::repoint_npx_entries() {
+        Ok(0) => {}
+        Ok(count) => info!("Pointed {} client entries at Fleur's npx shim", count),
+        Err(e) => error!(
+            "Failed to update client entries after repairing shims: {}",
+            e
+        ),
     }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-fn install_node() -> Result<(), String> {
+// Rewrites the shims already written, e.g. after the captured shell
+// environment they export changed
+pub fn rewrite_shims() {
     if is_test_mode() {
-        return Ok(());
+        return;
     }
 
-    match check_node_version() {
-        Ok(version) if version == NODE_VERSION => {
-            info!(
-                "Node.js {} is already installed, skipping installation",
-                NODE_VERSION
-            );
-            NODE_INSTALLED.store(true, Ordering::Relaxed);
-            return Ok(());
+    for shim in npx_shims().into_iter().filter(|shim| shim.is_file()) {
+        let Some(version) = npx_shim_version(&shim) else {
+            continue;
+        };
+        if let Err(e) = write_npx_shim(&shim, &version) {
+            warn!("Failed to rewrite NPX shim {}: {}", shim.display(), e);
+        }
+    }
+    if get_uvx_shim_path().is_file() {
+        if let Err(e) = ensure_uvx_shim() {
+            warn!("Failed to rewrite the UVX shim: {}", e);
         }
-        _ => {}
     }
+    for runtime in [&crate::runtimes::BUN, &crate::runtimes::DENO] {
+        if runtime.shim_path().is_file() {
+            if let Err(e) = crate::runtimes::ensure_runtime_shim(runtime) {
+                warn!("Failed to rewrite the {} shim: {}", runtime.name, e);
+            }
+        }
+    }
+}
 
-    info!("Installing Node.js {}", NODE_VERSION);
+// Installs the given Node.js if needed and writes the shim that runs its npx
+pub fn ensure_npx_shim_for(version: &str) -> Result<String, String> {
+    if version == default_node_version() {
+        return ensure_npx_shim();
+    }
 
-    if !check_nvm_installed() {
-        return Err("nvm is required to install Node.js".to_string());
+    let shim_path = get_npx_shim_path_for(version);
+    if is_test_mode() {
+        return Ok(shim_path.to_string_lossy().to_string());
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-          export NVM_DIR="$HOME/.nvm"
-          [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-          nvm install {} --no-progress
-      "#,
-            NODE_VERSION
-        );
+    install_node_version(version)?;
+    write_npx_shim(&shim_path, version)
+}
 
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to run node installation: {}", e))?;
+pub fn get_uvx_shim_path() -> std::path::PathBuf {
+    let shim_name = if cfg!(target_os = "windows") {
+        "uvx-fleur.cmd"
+    } else {
+        "uvx-fleur"
+    };
+    get_fleur_data_dir().join("bin").join(shim_name)
+}
 
-        if !output.status.success() {
-            return Err(format!(
-                "Node installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-    }
+// Keeps uv's Pythons, caches and tools inside Fleur's data dir so they don't
+// depend on (or clutter) the user's own uv setup
+pub(crate) fn uv_environment() -> Vec<(&'static str, String)> {
+    let data_dir = get_fleur_data_dir();
+    let dir = |path: &str| data_dir.join(path).to_string_lossy().to_string();
+    vec![
+        ("UV_PYTHON", DEFAULT_PYTHON_VERSION.to_string()),
+        ("UV_PYTHON_PREFERENCE", "only-managed".to_string()),
+        ("UV_PYTHON_INSTALL_DIR", dir("python")),
+        ("UV_CACHE_DIR", dir("cache/uv")),
+        ("UV_TOOL_DIR", dir("tools/uv")),
+    ]
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        let version_without_v = NODE_VERSION.trim_start_matches('v');
+pub fn uvx_shim_script(uvx_path: &str) -> String {
+    let uv_dir = std::path::Path::new(uvx_path)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
 
-        let output = Command::new("nvm")
-            .arg("install")
-            .arg(version_without_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to run node installation: {}", e))?;
+    if cfg!(target_os = "windows") {
+        let env: String = uv_environment()
+            .iter()
+            .map(|(name, value)| format!("set {}={}\n", name, value))
+            .collect();
+        format!(
+            r#"@echo off
+:: UVX shim for Fleur on Windows
+:: {marker} {format}
 
-        if !output.status.success() {
-            return Err(format!(
-                "Node installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
+set UVX={uvx}
+{env}set PATH={uv_dir};%SystemRoot%\System32;%SystemRoot%
 
-        let use_output = Command::new("nvm")
-            .arg("use")
-            .arg(version_without_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to set node version: {}", e))?;
+"%UVX%" %*
+"#,
+            marker = SHIM_FORMAT_MARKER,
+            format = SHIM_FORMAT,
+            uvx = uvx_path,
+            env = env,
+            uv_dir = uv_dir
+        )
+    } else {
+        let quote = crate::shell_env::shell_quote;
+        let env: String = uv_environment()
+            .iter()
+            .map(|(name, value)| format!("export {}={}\n", name, quote(value)))
+            .chain(std::iter::once(crate::shell_env::shim_exports()))
+            .collect();
+        // A PATH captured from the user's login shell stands in for the bare system one
+        let path = crate::shell_env::captured_path()
+            .unwrap_or_else(|| "/usr/bin:/bin:/usr/sbin:/sbin".to_string());
+        format!(
+            r#"#!/bin/sh
+# UVX shim for Fleur
+# {marker} {format}
 
-        if !use_output.status.success() {
-            return Err(format!(
-                "Failed to set node version: {}",
-                String::from_utf8_lossy(&use_output.stderr)
-            ));
-        }
+UVX="{uvx}"
 
-        let nvm_root = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-            .unwrap_or_default();
+{env}
+# Never the PATH of whatever launched the client
+export PATH={path}
 
-        let node_exists = nvm_root.join(version_without_v).join("node.exe").exists()
-            || nvm_root
-                .join(format!("v{}", version_without_v))
-                .join("node.exe")
-                .exists();
-
-        if !node_exists {
-            return Err(format!(
-                "Node.js {} installation verification failed. Binary not found at expected locations.",
-                NODE_VERSION
-            ));
-        }
+exec "$UVX" "$@"
+"#,
+            marker = SHIM_FORMAT_MARKER,
+            format = SHIM_FORMAT,
+            uvx = uvx_path,
+            env = env,
+            path = quote(&format!("{}:{}", uv_dir, path))
+        )
     }
+}
 
-    NODE_INSTALLED.store(true, Ordering::Relaxed);
-    info!("Node.js {} installed successfully", NODE_VERSION);
-    Ok(())
+// Fleur's pinned uvx, installing it if needed
+pub(crate) fn resolve_uvx() -> Result<String, String> {
+    let uvx = std::path::PathBuf::from(crate::runtimes::executable_name("uvx"));
+    let uv_dir = crate::runtimes::install_pinned("uv", UV_VERSION, &uvx)?;
+    Ok(uv_dir.join(uvx).to_string_lossy().to_string())
 }
 
-fn check_nvm_installed() -> bool {
+// Installs Fleur's uv if needed and (re)writes the shim uvx apps run through
+pub fn ensure_uvx_shim() -> Result<String, String> {
+    let shim_path = get_uvx_shim_path();
     if is_test_mode() {
-        return true;
+        debug!("Using test mode path for uvx shim");
+        return Ok("/test/.local/share/fleur/bin/uvx-fleur".to_string());
     }
 
-    // If we've already confirmed nvm is installed, return early
-    if NVM_INSTALLED.load(Ordering::Relaxed) {
-        debug!("NVM already confirmed as installed");
-        return true;
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        let nvm_dir = dirs::home_dir()
-            .map(|path| path.join(".nvm"))
-            .filter(|path| path.exists());
-
-        if nvm_dir.is_none() {
-            info!("NVM directory not found");
-            return false;
-        }
+    let script = uvx_shim_script(&resolve_uvx()?);
 
-        match check_nvm_version() {
-            Ok(version) => {
-                info!("NVM version {} is installed", version);
-                NVM_INSTALLED.store(true, Ordering::Relaxed);
-                true
-            }
-            Err(_) => {
-                info!("NVM directory exists but nvm command failed");
-                false
-            }
-        }
+    if std::fs::read_to_string(&shim_path).ok().as_deref() == Some(script.as_str()) {
+        debug!("UVX shim already exists at {}", shim_path.display());
+        return Ok(shim_path.to_string_lossy().to_string());
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        let nvm_home = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")));
-
-        if let Some(nvm_path) = nvm_home {
-            if nvm_path.exists() {
-                let nvm_exe = nvm_path.join("nvm.exe");
-                if nvm_exe.exists() {
-                    info!("NVM for Windows found at {}", nvm_path.display());
-                    NVM_INSTALLED.store(true, Ordering::Relaxed);
-                    return true;
-                }
-            }
-        }
-
-        info!("NVM for Windows not found");
-        false
+    if let Some(parent) = shim_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create shim directory: {}", e))?;
     }
+    std::fs::write(&shim_path, script)
+        .map_err(|e| format!("Failed to write shim script: {}", e))?;
+    crate::binaries::mark_executable(&shim_path)?;
+
+    info!("UVX shim created at {}", shim_path.display());
+    Ok(shim_path.to_string_lossy().to_string())
 }
 
-fn install_nvm() -> Result<(), String> {
+// uv keeps the Pythons it installs side by side and uvx picks the one matching
+// `--python`, so installing ahead of time just saves the first launch
+pub fn ensure_python(request: &str) -> Result<(), String> {
     if is_test_mode() {
         return Ok(());
     }
 
-    if check_nvm_installed() {
-        info!("nvm is already installed, skipping installation");
-        return Ok(());
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        info!("Installing nvm...");
-
-        let shell_command = r#"
-          curl -o- https://raw.githubusercontent.com/nvm-sh/nvm/v0.40.1/install.sh | bash
-      "#;
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to install nvm: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "nvm installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
+    let uv_path = std::path::Path::new(&resolve_uvx()?)
+        .with_file_name(crate::runtimes::executable_name("uv"));
+    info!("Installing Python {}", request);
+    // Into the same place the uvx shim looks for its Pythons
+    let output = create_windowless_command(&uv_path.to_string_lossy())
+        .args(["python", "install", request])
+        .envs(uv_environment())
+        .output()
+        .map_err(|e| format!("Failed to run uv: {}", e))?;
 
-        NVM_INSTALLED.store(true, Ordering::Relaxed);
-        info!("nvm installed successfully");
-        Ok(())
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to install Python {}: {}",
+            request,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
     }
+    Ok(())
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        info!("Installing nvm for Windows...");
-
-        let temp_dir = std::env::temp_dir().join("fleur_nvm_install");
-        let _ = std::fs::create_dir_all(&temp_dir);
-        let installer_path = temp_dir.join("nvm-setup.exe");
-
-        let nvm_installer_url =
-            "https://github.com/coreybutler/nvm-windows/releases/download/1.1.11/nvm-setup.exe";
-
-        let download_cmd = format!(
-            "Invoke-WebRequest -Uri '{}' -OutFile '{}'",
-            nvm_installer_url,
-            installer_path.to_string_lossy()
-        );
-
-        let dl_output = Command::new("powershell")
-            .arg("-Command")
-            .arg(&download_cmd)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to download nvm installer: {}", e))?;
+fn check_node_installed() -> bool {
+    if is_test_mode() {
+        return true;
+    }
 
-        if !dl_output.status.success() {
-            return Err(format!(
-                "Failed to download nvm installer: {}",
-                String::from_utf8_lossy(&dl_output.stderr)
-            ));
+    // Looked up every time since the range setting can change underneath us
+    match get_node_paths() {
+        Ok((node_path, _)) => {
+            debug!("Node.js is installed at {}", node_path);
+            true
         }
-
-        info!("Starting NVM for Windows installer. Please follow the on-screen instructions.");
-        let installer_output = Command::new(&installer_path)
-            .arg("/SILENT")
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to run nvm installer: {}", e))?;
-
-        if !installer_output.status.success() {
-            return Err(format!(
-                "NVM installation failed: {}",
-                String::from_utf8_lossy(&installer_output.stderr)
-            ));
+        Err(e) => {
+            info!("{}", e);
+            false
         }
+    }
+}
 
-        let _ = std::fs::remove_file(&installer_path);
-        let _ = std::fs::remove_dir(&temp_dir);
+fn install_node() -> Result<(), String> {
+    if is_test_mode() {
+        return Ok(());
+    }
 
-        if check_nvm_installed() {
-            NVM_INSTALLED.store(true, Ordering::Relaxed);
-            info!("nvm for Windows installed successfully");
-            Ok(())
-        } else {
-            Err("nvm for Windows installation completed but verification failed".to_string())
-        }
+    if check_node_installed() {
+        info!("A compatible Node.js is already installed, skipping installation");
+        return Ok(());
     }
+
+    install_node_version(&default_node_version())
+}
+
+fn install_node_version(version: &str) -> Result<(), String> {
+    let (node, _) = managed_node_executables();
+    let node_dir = crate::runtimes::install_pinned("node", version, &node)?;
+    info!("Node.js {} is installed in {}", version, node_dir.display());
+    Ok(())
 }
 
 fn check_uv_installed() -> bool {
@@ -828,14 +903,8 @@ fn check_uv_installed() -> bool {
         return true;
     }
 
-    if UV_INSTALLED.load(Ordering::Relaxed) {
-        debug!("uv already confirmed as installed");
-        return true;
-    }
-
     if find_existing_uvx().is_some() {
         info!("uvx found, assuming uv is already installed");
-        UV_INSTALLED.store(true, Ordering::Relaxed);
         return true;
     }
 
@@ -872,7 +941,6 @@ fn check_uv_installed() -> bool {
         Ok(output) if output.status.success() => {
             let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
             info!("uv version {} is installed", version);
-            UV_INSTALLED.store(true, Ordering::Relaxed);
             true
         }
         _ => {
@@ -894,120 +962,10 @@ fn install_uv() -> Result<(), String> {
 
     info!("Installing uv...");
 
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = r#"
-          curl -LsSf https://astral.sh/uv/install.sh | sh
-      "#;
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to install uv: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "uv installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-
-        let source_cargo_env = r#"
-          source "$HOME/.cargo/env"
-      "#;
-
-        let _ = Command::new("bash")
-            .arg("-c")
-            .arg(source_cargo_env)
-            .output();
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let ps_command = r#"
-          irm -Uri "https://astral.sh/uv/install.ps1" | iex
-      "#;
-
-        let output = Command::new("powershell")
-            .arg("-ExecutionPolicy")
-            .arg("Bypass")
-            .arg("-Command")
-            .arg(ps_command)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to install uv: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "uv installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-    }
-
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-
-    #[cfg(target_os = "macos")]
-    let uv_path = home_dir.join(".cargo/bin/uv");
-
-    #[cfg(target_os = "windows")]
-    let uv_path = home_dir.join(".cargo").join("bin").join("uv.exe");
-
-    #[cfg(target_os = "macos")]
-    let uvx_path = home_dir.join(".cargo/bin/uvx");
-
-    #[cfg(target_os = "windows")]
-    let uvx_path = home_dir.join(".cargo").join("bin").join("uvx.exe");
-
-    if uv_path.exists() {
-        info!("uv found at {}", uv_path.display());
-        if !uvx_path.exists() {
-            info!(
-                "uvx not found at {} after uv installation, this is unexpected",
-                uvx_path.display()
-            );
-        } else {
-            info!("uvx found at {}", uvx_path.display());
-        }
-    } else {
-        #[cfg(target_os = "macos")]
-        let which_command = "which";
-
-        #[cfg(target_os = "windows")]
-        let which_command = "where";
-
-        #[cfg(target_os = "macos")]
-        let which_output = Command::new(which_command).arg("uv").output();
-
-        #[cfg(target_os = "windows")]
-        let which_output = Command::new(which_command)
-            .arg("uv.exe")
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-
-        match which_output {
-            Ok(output) if output.status.success() => {
-                let path = String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .next()
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-                if !path.is_empty() {
-                    info!("uv installed at {} (not in the expected location)", path);
-                } else {
-                    info!("uv not found in PATH after installation");
-                }
-            }
-            _ => {
-                info!("uv not found in PATH after installation");
-            }
-        }
-    }
+    let uvx = std::path::PathBuf::from(crate::runtimes::executable_name("uvx"));
+    let uv_dir = crate::runtimes::install_pinned("uv", UV_VERSION, &uvx)?;
 
-    UV_INSTALLED.store(true, Ordering::Relaxed);
-    info!("uv installation completed");
+    info!("uv {} installed to {}", UV_VERSION, uv_dir.display());
     Ok(())
 }
 
@@ -1016,152 +974,195 @@ fn ensure_node_environment() -> Result<String, String> {
         return Ok("Node environment is ready".to_string());
     }
 
-    if !check_nvm_installed() {
-        install_nvm()?;
-    }
-
-    match check_node_version() {
-        Ok(version) => {
-            if version != NODE_VERSION {
-                info!(
-                    "Node.js {} found, but {} required. Installing...",
-                    version, NODE_VERSION
-                );
-                install_node()?;
-            } else {
-                debug!("Node.js {} is already installed", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::Relaxed);
-            }
-        }
-        Err(_) => {
-            info!("Node.js not found. Installing...");
-            install_node()?;
-        }
-    }
-
+    install_node()?;
     ensure_npx_shim()?;
 
-    ENVIRONMENT_SETUP_COMPLETED.store(true, Ordering::SeqCst);
-
     Ok("Node environment is ready".to_string())
 }
 
-pub fn ensure_environment_sync() -> Result<String, String> {
-    if is_test_mode() {
-        return Ok("Environment setup completed".to_string());
-    }
-
-    if ENVIRONMENT_SETUP_COMPLETED.load(Ordering::SeqCst) {
-        debug!("Environment setup already completed");
-        return Ok("Environment setup already completed".to_string());
+fn install_environment() -> Result<(), String> {
+    if let Err(e) = ensure_uvx_shim() {
+        error!("Failed to set up uv: {}", e);
+        return Err(e);
     }
 
-    info!("Starting synchronous environment setup");
+    ensure_node_environment()
+        .inspect_err(|e| error!("Failed to ensure node environment: {}", e))?;
 
-    let _lock = match ENVIRONMENT_SETUP_LOCK.try_lock() {
-        Ok(guard) => guard,
-        Err(_) => {
-            info!("Another environment setup is already in progress, waiting...");
-            ENVIRONMENT_SETUP_LOCK.lock().unwrap()
+    // Only some apps need Bun or Deno, so setup still succeeds without them and
+    // installing such an app retries them
+    for runtime in [&crate::runtimes::BUN, &crate::runtimes::DENO] {
+        if let Err(e) = crate::runtimes::ensure_runtime_shim(runtime) {
+            warn!("Failed to set up {}: {}", runtime.name, e);
         }
-    };
+    }
+    Ok(())
+}
 
-    if ENVIRONMENT_SETUP_COMPLETED.load(Ordering::SeqCst) {
-        return Ok("Environment setup completed while waiting".to_string());
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", content = "detail", rename_all = "camelCase")]
+pub enum EnvironmentState {
+    Unknown,
+    Checking,
+    Installing(SetupProgress),
+    Ready,
+    Failed(String),
+}
+
+impl EnvironmentState {
+    pub fn is_running(&self) -> bool {
+        matches!(
+            self,
+            EnvironmentState::Checking | EnvironmentState::Installing(_)
+        )
     }
+}
 
-    let mut has_critical_error = false;
+// Owns the uv and Node.js setup. Only one caller runs it at a time, everyone
+// arriving meanwhile waits for that run's outcome. A failed run is retried by
+// the next caller.
+pub struct EnvironmentManager {
+    state: Mutex<EnvironmentState>,
+    changed: Condvar,
+    waiting: AtomicUsize,
+}
 
-    if find_existing_uvx().is_none() {
-        if !check_uv_installed() {
-            if let Err(e) = install_uv() {
-                error!("Failed to install uv: {}", e);
-                has_critical_error = true;
-            }
-        }
-    } else {
-        info!("uvx is already installed, skipping uv installation");
+impl Default for EnvironmentManager {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    if !has_critical_error {
-        if let Err(e) = ensure_node_environment() {
-            error!("Failed to ensure node environment: {}", e);
-            has_critical_error = true;
+impl EnvironmentManager {
+    pub fn new() -> Self {
+        EnvironmentManager {
+            state: Mutex::new(EnvironmentState::Unknown),
+            changed: Condvar::new(),
+            waiting: AtomicUsize::new(0),
         }
     }
 
-    if !has_critical_error {
-        ENVIRONMENT_SETUP_COMPLETED.store(true, Ordering::SeqCst);
-        info!("Synchronous environment setup completed successfully");
-        Ok("Environment setup completed".to_string())
-    } else {
-        info!("Synchronous environment setup completed with errors");
-        Err("Environment setup failed with errors".to_string())
+    pub fn state(&self) -> EnvironmentState {
+        self.state.lock().unwrap().clone()
     }
-}
 
-#[tauri::command]
-pub async fn ensure_environment() -> Result<String, String> {
-    if is_test_mode() {
-        return Ok("Environment setup started".to_string());
+    // Callers blocked on a running setup
+    pub fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
     }
 
-    if ENVIRONMENT_SETUP_STARTED.swap(true, Ordering::SeqCst) {
-        info!("Environment setup already in progress, skipping");
-        return Ok("Environment setup already in progress".to_string());
+    fn set_state(&self, state: EnvironmentState) {
+        *self.state.lock().unwrap() = state;
+        self.changed.notify_all();
     }
 
-    match tauri::async_runtime::spawn_blocking(|| {
-        let _lock = match ENVIRONMENT_SETUP_LOCK.try_lock() {
-            Ok(guard) => guard,
-            Err(_) => {
-                info!("Another environment setup is already in progress");
-                ENVIRONMENT_SETUP_STARTED.store(false, Ordering::SeqCst);
-                return Err("Another environment setup is already in progress".to_string());
-            }
-        };
+    // Progress only moves a running setup along, runtime installs for single
+    // apps happen while the environment is already Ready. Returns whether a
+    // setup was running.
+    fn record_progress(&self, progress: &SetupProgress) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.is_running() {
+            return false;
+        }
+        *state = EnvironmentState::Installing(progress.clone());
+        true
+    }
 
-        info!("Starting environment setup");
-        let mut setup_failed = false;
+    // Forgets a finished setup so the next caller runs it again. A running
+    // setup is left alone and callers wait for it as usual.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !state.is_running() {
+            *state = EnvironmentState::Unknown;
+        }
+    }
 
-        if find_existing_uvx().is_none() {
-            if !check_uv_installed() {
-                if let Err(e) = install_uv() {
-                    error!("Failed to install uv: {}", e);
-                    setup_failed = true;
+    pub fn ensure_ready_with(
+        &self,
+        setup: impl FnOnce() -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let mut waited = false;
+        loop {
+            match &*state {
+                EnvironmentState::Ready => return Ok(()),
+                EnvironmentState::Failed(reason) if waited => return Err(reason.clone()),
+                EnvironmentState::Unknown | EnvironmentState::Failed(_) => break,
+                EnvironmentState::Checking | EnvironmentState::Installing(_) => {
+                    debug!("Environment setup already in progress, waiting for it");
+                    waited = true;
+                    self.waiting.fetch_add(1, Ordering::SeqCst);
+                    state = self.changed.wait(state).unwrap();
+                    self.waiting.fetch_sub(1, Ordering::SeqCst);
                 }
             }
-        } else {
-            info!("uvx is already installed, skipping uv installation");
         }
+        *state = EnvironmentState::Checking;
+        drop(state);
 
-        if !setup_failed {
-            if let Err(e) = ensure_node_environment() {
-                error!("Failed to ensure node environment: {}", e);
-                setup_failed = true;
+        info!("Starting environment setup");
+        report_progress(SetupProgress::new(
+            SetupStage::Checking,
+            "Checking uv and Node.js",
+        ));
+        // A panic mustn't leave waiters stuck on Checking
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(setup))
+            .unwrap_or_else(|_| Err("Environment setup failed unexpectedly".to_string()));
+
+        match &result {
+            Ok(()) => {
+                info!("Environment setup completed successfully");
+                report_progress(SetupProgress::new(
+                    SetupStage::Done,
+                    "uv and Node.js are ready",
+                ));
+                self.set_state(EnvironmentState::Ready);
+            }
+            Err(e) => {
+                error!("Environment setup failed: {}", e);
+                report_progress(SetupProgress::new(SetupStage::Failed, e.clone()));
+                self.set_state(EnvironmentState::Failed(e.clone()));
             }
         }
+        result
+    }
+}
 
-        ENVIRONMENT_SETUP_STARTED.store(false, Ordering::SeqCst);
+static ENVIRONMENT: Lazy<EnvironmentManager> = Lazy::new(EnvironmentManager::new);
 
-        if setup_failed {
-            ENVIRONMENT_SETUP_COMPLETED.store(false, Ordering::SeqCst);
-            Err("Environment setup failed. Please check the logs for details.".to_string())
-        } else {
-            ENVIRONMENT_SETUP_COMPLETED.store(true, Ordering::SeqCst);
-            info!("Environment setup completed successfully");
-            Ok("Environment setup completed".to_string())
-        }
-    })
-    .await
-    {
-        Ok(result) => result,
-        Err(e) => {
-            error!("Environment setup task panicked: {}", e);
-            ENVIRONMENT_SETUP_STARTED.store(false, Ordering::SeqCst);
-            ENVIRONMENT_SETUP_COMPLETED.store(false, Ordering::SeqCst);
-            Err("Environment setup failed unexpectedly".to_string())
-        }
+pub fn environment_manager() -> &'static EnvironmentManager {
+    &ENVIRONMENT
+}
+
+// The one way in for everything that needs uv and Node.js in place
+pub fn ensure_ready() -> Result<(), String> {
+    if is_test_mode() {
+        return Ok(());
+    }
+    ENVIRONMENT.ensure_ready_with(install_environment)
+}
+
+// Runs setup again even after it succeeded, for runtimes that went missing since
+pub fn rerun_setup() -> Result<(), String> {
+    ENVIRONMENT.reset();
+    ensure_ready()
+}
+
+#[tauri::command]
+pub async fn ensure_environment() -> Result<String, String> {
+    // The UI calls this on startup, a good moment to catch shims gone stale
+    tauri::async_runtime::spawn_blocking(|| ensure_ready().map(|_| ensure_healthy_shims()))
+        .await
+        .map_err(|e| format!("Environment setup failed unexpectedly: {}", e))??;
+    Ok("Environment setup completed".to_string())
+}
+
+// Lets a window opened mid-setup pick up where the progress events left off
+#[tauri::command]
+pub fn get_environment_status() -> EnvironmentStatus {
+    EnvironmentStatus {
+        state: ENVIRONMENT.state(),
+        last_progress: LAST_PROGRESS.lock().unwrap().clone(),
     }
 }
 
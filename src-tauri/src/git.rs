@@ -0,0 +1,277 @@
+use crate::registry::GitSource;
+use log::{debug, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const GIT_RUNTIME: &str = "git";
+const GIT_DIR: &str = "git";
+const INTERPRETERS: [&str; 2] = ["node", "python"];
+// Lines of build output kept in error messages, the full output goes to the build log
+const ERROR_OUTPUT_LINES: usize = 20;
+const URL_SCHEMES: [&str; 3] = ["https://", "ssh://", "git://"];
+
+// scp-like ssh addresses such as git@github.com:acme/mcp.git
+fn is_scp_address(url: &str) -> bool {
+    let Some((user_host, path)) = url.split_once(':') else {
+        return false;
+    };
+    let Some((user, host)) = user_host.split_once('@') else {
+        return false;
+    };
+    let plain = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    };
+    plain(user) && plain(host) && !path.is_empty() && !path.starts_with('-')
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    if url.trim().is_empty() {
+        return Err("config.git.url is empty".to_string());
+    }
+    if url.starts_with('-') {
+        return Err(format!("Invalid repository URL '{}'", url));
+    }
+    let allowed = URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+        || is_scp_address(url)
+        // Tests clone from repositories in temporary directories
+        || (crate::environment::is_test_mode() && Path::new(url).is_absolute());
+    if !allowed {
+        return Err(format!(
+            "Unsupported repository URL '{}', use an https, ssh or git URL",
+            url
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_source(source: &GitSource) -> Result<(), String> {
+    validate_url(&source.url)?;
+    if let Some(git_ref) = &source.git_ref {
+        if git_ref.trim().is_empty() || git_ref.starts_with('-') {
+            return Err(format!("Invalid git ref '{}'", git_ref));
+        }
+    }
+    let entrypoint = Path::new(&source.entrypoint);
+    if source.entrypoint.trim().is_empty()
+        || entrypoint.is_absolute()
+        || entrypoint.components().any(|c| c.as_os_str() == "..")
+    {
+        return Err("config.git.entrypoint must be a path inside the repository".to_string());
+    }
+    if let Some(interpreter) = &source.interpreter {
+        if !INTERPRETERS.contains(&interpreter.as_str()) {
+            return Err(format!(
+                "Unknown interpreter '{}', expected one of {}",
+                interpreter,
+                INTERPRETERS.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Keys come from registries and end up in paths that are deleted and rebuilt,
+// so anything but a single plain path component is refused
+pub fn checkout_dir(mcp_key: &str) -> Result<PathBuf, String> {
+    if !crate::binaries::is_plain_file_name(mcp_key) {
+        return Err(format!("Invalid checkout name '{}'", mcp_key));
+    }
+    Ok(crate::environment::get_fleur_data_dir()
+        .join(GIT_DIR)
+        .join(mcp_key))
+}
+
+pub fn build_log_path(mcp_key: &str) -> Result<PathBuf, String> {
+    checkout_dir(mcp_key).map(|dir| dir.with_file_name(format!("{}.build.log", mcp_key)))
+}
+
+// Holds the commit of the last successful build so unchanged checkouts aren't rebuilt
+fn build_marker_path(mcp_key: &str) -> Result<PathBuf, String> {
+    checkout_dir(mcp_key).map(|dir| dir.with_file_name(format!("{}.built", mcp_key)))
+}
+
+fn venv_python(checkout: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        checkout.join(".venv").join("Scripts").join("python.exe")
+    } else {
+        checkout.join(".venv").join("bin").join("python")
+    }
+}
+
+// The command and args that run the built entrypoint of a checkout
+pub fn resolve_entrypoint(
+    source: &GitSource,
+    checkout: &Path,
+    args: &[String],
+) -> Result<(String, Vec<String>), String> {
+    let entrypoint = checkout
+        .join(&source.entrypoint)
+        .to_string_lossy()
+        .to_string();
+
+    let command = match source.interpreter.as_deref() {
+        None => return Ok((entrypoint, args.to_vec())),
+        Some("node") => crate::environment::get_node_paths()?.0,
+        Some(_) => venv_python(checkout).to_string_lossy().to_string(),
+    };
+
+    let mut command_args = vec![entrypoint];
+    command_args.extend(args.iter().cloned());
+    Ok((command, command_args))
+}
+
+fn run_git(args: &[&str], cwd: &Path) -> Result<String, String> {
+    debug!("Running git {:?} in {}", args, cwd.display());
+    // The ext:: transport runs arbitrary commands
+    let output = crate::environment::create_windowless_command("git")
+        .args(["-c", "protocol.ext.allow=never"])
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Node and the pinned uv from Fleur's environment come first on the PATH so
+// build steps like `npm ci` and `uv sync` use them
+fn build_path() -> Option<std::ffi::OsString> {
+    let mut dirs = Vec::new();
+    if !crate::environment::is_test_mode() {
+        if let Ok((node_path, _)) = crate::environment::get_node_paths() {
+            dirs.extend(Path::new(&node_path).parent().map(Path::to_path_buf));
+        }
+        match crate::environment::resolve_uvx() {
+            Ok(uvx_path) => dirs.extend(Path::new(&uvx_path).parent().map(Path::to_path_buf)),
+            Err(e) => warn!("Building without Fleur's uv: {}", e),
+        }
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    std::env::join_paths(dirs).ok()
+}
+
+fn run_build_step(step: &str, checkout: &Path, log: &mut String) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = crate::environment::create_windowless_command("cmd");
+        command.arg("/C").arg(step);
+        command
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = crate::environment::create_windowless_command("sh");
+        command.arg("-c").arg(step);
+        command
+    };
+
+    if let Some(path) = build_path() {
+        command.env("PATH", path);
+    }
+    // uv keeps its Pythons and caches in Fleur's data dir like the uvx shim does
+    command.envs(crate::environment::uv_environment());
+
+    info!("Running build step '{}' in {}", step, checkout.display());
+    let output = command
+        .current_dir(checkout)
+        .output()
+        .map_err(|e| format!("Failed to run build step '{}': {}", step, e))?;
+
+    let step_output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    log.push_str(&format!("$ {}\n{}\n", step, step_output));
+
+    if !output.status.success() {
+        let lines: Vec<&str> = step_output.lines().collect();
+        let tail = lines[lines.len().saturating_sub(ERROR_OUTPUT_LINES)..].join("\n");
+        return Err(format!(
+            "Build step '{}' failed ({}): {}",
+            step, output.status, tail
+        ));
+    }
+    Ok(())
+}
+
+// Clones or fetches the repository at the configured ref and rebuilds it when
+// the checked out commit changed. Installs and updates both go through here.
+pub fn ensure_checkout(mcp_key: &str, source: &GitSource) -> Result<PathBuf, String> {
+    let checkout = checkout_dir(mcp_key)?;
+    let parent = checkout
+        .parent()
+        .ok_or_else(|| format!("Invalid checkout path {}", checkout.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+
+    // Sources are checked when the registry is parsed, this covers any other caller
+    validate_source(source)?;
+
+    if checkout.join(".git").is_dir() {
+        run_git(
+            &["remote", "set-url", "--", "origin", &source.url],
+            &checkout,
+        )?;
+    } else {
+        if checkout.exists() {
+            fs::remove_dir_all(&checkout)
+                .map_err(|e| format!("Failed to remove {}: {}", checkout.display(), e))?;
+        }
+        info!("Cloning {} into {}", source.url, checkout.display());
+        run_git(
+            &[
+                "clone",
+                "--no-checkout",
+                "--",
+                &source.url,
+                &checkout.to_string_lossy(),
+            ],
+            parent,
+        )?;
+    }
+
+    let git_ref = source.git_ref.as_deref().unwrap_or("HEAD");
+    run_git(&["fetch", "--", "origin", git_ref], &checkout)?;
+    run_git(
+        &["checkout", "--force", "--detach", "FETCH_HEAD"],
+        &checkout,
+    )?;
+    let commit = run_git(&["rev-parse", "HEAD"], &checkout)?;
+
+    let marker = build_marker_path(mcp_key)?;
+    if fs::read_to_string(&marker).ok().as_deref() == Some(commit.as_str()) {
+        debug!("{} is already built at {}", mcp_key, commit);
+        return Ok(checkout);
+    }
+    let _ = fs::remove_file(&marker);
+
+    let mut log = String::new();
+    let result = source
+        .build
+        .iter()
+        .try_for_each(|step| run_build_step(step, &checkout, &mut log));
+
+    let log_path = build_log_path(mcp_key)?;
+    if let Err(e) = fs::write(&log_path, &log) {
+        debug!("Failed to write build log {}: {}", log_path.display(), e);
+    }
+    result?;
+
+    fs::write(&marker, &commit)
+        .map_err(|e| format!("Failed to write {}: {}", marker.display(), e))?;
+    info!("Built {} at {}", mcp_key, commit);
+    Ok(checkout)
+}
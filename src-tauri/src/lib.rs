@@ -1,8 +1,22 @@
 pub mod app;
+pub mod binaries;
 pub mod clients;
+pub mod constraints;
+pub mod container;
+pub mod custom_apps;
+pub mod doctor;
 pub mod environment;
 pub mod file_utils;
+pub mod git;
 pub mod os;
+pub mod registry;
+pub mod runtimes;
+pub mod search;
+pub mod settings;
+pub mod shell_env;
+pub mod signature;
+pub mod snippet;
+pub mod tool_budget;
 
 use log::{error, info};
 use simplelog::{ConfigBuilder, LevelFilter, WriteLogger};
@@ -195,12 +209,37 @@ pub fn run() {
             app::set_client_config_path,
             app::get_client_config_path,
             app::refresh_app_registry,
+            app::get_outdated_apps,
+            app::update_app,
+            custom_apps::get_custom_apps,
+            custom_apps::install_custom_app,
+            custom_apps::remove_custom_app,
+            doctor::diagnose_environment,
+            doctor::fix_environment_issue,
+            registry::get_registry_warnings,
+            registry::get_registry_info,
+            registry::get_configured_registry_sources,
+            registry::set_registry_sources,
+            search::search_apps,
+            snippet::parse_mcp_snippet,
+            snippet::install_mcp_snippet,
             environment::ensure_environment,
+            environment::get_environment_status,
+            settings::get_settings,
+            settings::update_setting,
+            shell_env::capture_shell_environment,
+            shell_env::get_shell_environment,
+            shell_env::clear_shell_environment,
+            tool_budget::get_tool_budget_report,
+            tool_budget::check_tool_budget,
             log_from_frontend,
             open_system_url,
             open_logs_folder,
         ])
         .setup(|app| {
+            environment::set_progress_handle(app.handle().clone());
+            registry::start_background_refresh(app.handle().clone());
+
             let handle = app.handle().clone();
             info!("Checking for updates...");
             tauri::async_runtime::spawn(async move {
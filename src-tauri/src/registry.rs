@@ -0,0 +1,1095 @@
+use crate::constraints;
+use crate::settings;
+use crate::signature::{self, TrustedKey, SIGNATURE_EXTENSION};
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::Emitter;
+
+pub const DEFAULT_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/fleuristes/app-registry/refs/heads/main/apps.json";
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(15);
+pub const REGISTRY_UPDATED_EVENT: &str = "registry-updated";
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 60 * 60;
+const MIN_REFRESH_INTERVAL_SECS: u64 = 60;
+
+const CACHE_FILE: &str = "registry.json";
+const CACHE_METADATA_FILE: &str = "registry.meta.json";
+
+lazy_static! {
+    static ref REGISTRY_WARNINGS: Mutex<Vec<RegistryWarning>> = Mutex::new(Vec::new());
+    static ref REGISTRY_STATUS: Mutex<Vec<RegistrySourceStatus>> = Mutex::new(Vec::new());
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IconUrls {
+    pub light: String,
+    pub dark: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppIcon {
+    #[serde(rename = "type")]
+    pub icon_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<IconUrls>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Feature {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub prompt: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetupItem {
+    pub label: String,
+    #[serde(rename = "type")]
+    pub setup_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub key: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RuntimeRequirements {
+    // Semver ranges such as ">=18" or ">=3.10, <3.13"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<String>,
+    // Executables that must be on the PATH
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<String>,
+}
+
+impl RuntimeRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.node.is_none() && self.python.is_none() && self.binaries.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BinaryDownload {
+    pub url: String,
+    pub sha256: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitSource {
+    pub url: String,
+    // Branch, tag or commit to build, the remote's default branch when missing
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    // Shell commands run in the checkout, e.g. "npm ci" and "npm run build"
+    #[serde(default)]
+    pub build: Vec<String>,
+    // Path of the built server relative to the checkout
+    pub entrypoint: String,
+    // "node" or "python" to run the entrypoint with, run directly when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    // Mounts such as "${WORKSPACE}:/workspace:ro", `${VAR}` is filled from the app's env
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryAppConfig {
+    pub mcp_key: String,
+    pub runtime: String,
+    pub args: Vec<String>,
+    // Platforms the server runs on ("macos", "windows", "linux"), all when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
+    #[serde(default, skip_serializing_if = "RuntimeRequirements::is_empty")]
+    pub requires: RuntimeRequirements,
+    // Replaces `args` on the given platform
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub platform_args: BTreeMap<String, Vec<String>>,
+    // Image and mounts for the "docker" runtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerSpec>,
+    // Prebuilt releases for the "binary" runtime, keyed by "<platform>-<arch>"
+    // such as "macos-aarch64" or "windows-x86_64"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub downloads: BTreeMap<String, BinaryDownload>,
+    // Repository and build steps for the "git" runtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitSource>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryApp {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<AppIcon>,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub price: String,
+    #[serde(default)]
+    pub developer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<Feature>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup: Vec<SetupItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<EnvVar>,
+    pub config: RegistryAppConfig,
+    // Name of the registry source the entry was loaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    // Name of the pinned key that signed the entry's registry document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    // Fields Fleur doesn't model yet are passed through to the frontend untouched
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryWarning {
+    pub index: usize,
+    pub name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryDelta {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl RegistryDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheMetadata {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: u64,
+    // The signature the cached body was verified with, for signed sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RegistrySource {
+    Network,
+    Cache,
+    File,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryInfo {
+    pub url: String,
+    pub source: RegistrySource,
+    pub fetched_at: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySourceConfig {
+    pub name: String,
+    // An HTTPS URL, a JSON file, or a directory of JSON files
+    pub location: String,
+    // Higher priority sources win when several define the same app name
+    #[serde(default)]
+    pub priority: i32,
+    // Signed sources are refused unless a detached minisign signature
+    // made by one of `public_keys` matches the document
+    #[serde(default)]
+    pub signed: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_keys: Vec<TrustedKey>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySourceStatus {
+    pub name: String,
+    pub location: String,
+    pub priority: i32,
+    pub app_count: usize,
+    pub source: Option<RegistrySource>,
+    pub fetched_at: Option<u64>,
+    pub signers: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SourceLocation {
+    Url(String),
+    File(PathBuf),
+    Directory(PathBuf),
+}
+
+struct SourceDocument {
+    entries: Vec<Value>,
+    source: RegistrySource,
+    fetched_at: u64,
+    error: Option<String>,
+    signers: Vec<String>,
+}
+
+enum FetchOutcome {
+    NotModified,
+    Modified {
+        body: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn get_registry_cache_dir() -> PathBuf {
+    crate::environment::get_fleur_data_dir().join("registry")
+}
+
+pub fn default_registry_sources() -> Vec<RegistrySourceConfig> {
+    vec![RegistrySourceConfig {
+        name: "fleur".to_string(),
+        location: DEFAULT_REGISTRY_URL.to_string(),
+        priority: 0,
+        signed: false,
+        public_keys: Vec::new(),
+    }]
+}
+
+pub fn get_registry_sources() -> Vec<RegistrySourceConfig> {
+    settings::get_setting("registrySources")
+        .and_then(|value| match serde_json::from_value(value) {
+            Ok(sources) => Some(sources),
+            Err(e) => {
+                warn!("Ignoring invalid registrySources setting: {}", e);
+                None
+            }
+        })
+        .unwrap_or_else(default_registry_sources)
+}
+
+fn is_loopback_url(rest: &str) -> bool {
+    let host = rest.split(['/', ':']).next().unwrap_or("");
+    host == "localhost" || host == "127.0.0.1"
+}
+
+fn resolve_location(location: &str) -> Result<SourceLocation, String> {
+    if location.starts_with("https://") {
+        return Ok(SourceLocation::Url(location.to_string()));
+    }
+
+    if let Some(rest) = location.strip_prefix("http://") {
+        // Plain HTTP is only acceptable for registries served from this machine
+        if is_loopback_url(rest) {
+            return Ok(SourceLocation::Url(location.to_string()));
+        }
+        return Err(format!("Registry source must use HTTPS: {}", location));
+    }
+
+    let path = PathBuf::from(location.strip_prefix("file://").unwrap_or(location));
+    if path.is_dir() {
+        Ok(SourceLocation::Directory(path))
+    } else if path.is_file() {
+        Ok(SourceLocation::File(path))
+    } else {
+        Err(format!("Registry source not found: {}", path.display()))
+    }
+}
+
+// Keyed on a hash so names that only differ in punctuation get their own cache
+pub fn source_cache_dir(cache_root: &Path, name: &str) -> PathBuf {
+    cache_root.join(crate::binaries::sha256_hex(name.as_bytes()))
+}
+
+fn signature_path(path: &Path) -> PathBuf {
+    let mut signature_path = path.as_os_str().to_owned();
+    signature_path.push(format!(".{}", SIGNATURE_EXTENSION));
+    PathBuf::from(signature_path)
+}
+
+// Checks `body` against its detached signature when the source requires one
+// and returns the name of the key that signed it.
+fn verify_document(
+    source: &RegistrySourceConfig,
+    body: &str,
+    signature: impl FnOnce() -> Result<String, String>,
+) -> Result<Option<String>, String> {
+    if !source.signed {
+        return Ok(None);
+    }
+
+    let signature = signature()
+        .map_err(|e| format!("Refusing unsigned registry from '{}': {}", source.name, e))?;
+    let signer = signature::verify_detached(body.as_bytes(), &signature, &source.public_keys)
+        .map_err(|e| format!("Refusing registry from '{}': {}", source.name, e))?;
+
+    info!("Registry source '{}' is signed by {}", source.name, signer);
+    Ok(Some(signer))
+}
+
+// Unsigned documents can't vouch for a signer, so whatever the entry claims is replaced
+fn tag_signer(entries: &mut [Value], signer: Option<&str>) {
+    for entry in entries.iter_mut().filter_map(|e| e.as_object_mut()) {
+        match signer {
+            Some(signer) => entry.insert("signer".to_string(), json!(signer)),
+            None => entry.remove("signer"),
+        };
+    }
+}
+
+fn read_registry_file(
+    path: &Path,
+    source: &RegistrySourceConfig,
+) -> Result<(Vec<Value>, Option<String>), String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let signer = verify_document(source, &contents, || {
+        let signature_path = signature_path(path);
+        fs::read_to_string(&signature_path)
+            .map_err(|e| format!("Failed to read {}: {}", signature_path.display(), e))
+    })?;
+    let document: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let mut entries = match document {
+        Value::Array(entries) => entries,
+        Value::Object(_) => vec![document],
+        _ => {
+            return Err(format!(
+                "{} must contain an app or an array of apps",
+                path.display()
+            ))
+        }
+    };
+    tag_signer(&mut entries, signer.as_deref());
+    Ok((entries, signer))
+}
+
+fn read_registry_directory(
+    dir: &Path,
+    source: &RegistrySourceConfig,
+) -> Result<(Vec<Value>, Vec<String>), String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut entries = Vec::new();
+    let mut signers = Vec::new();
+    for file in files {
+        let (file_entries, signer) = read_registry_file(&file, source)?;
+        entries.extend(file_entries);
+        signers.extend(signer);
+    }
+    signers.sort();
+    signers.dedup();
+    Ok((entries, signers))
+}
+
+fn file_modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_else(now_secs)
+}
+
+fn fetch_source(
+    source: &RegistrySourceConfig,
+    cache_root: &Path,
+) -> Result<SourceDocument, String> {
+    match resolve_location(&source.location)? {
+        SourceLocation::Url(url) => {
+            let cache_dir = source_cache_dir(cache_root, &source.name);
+            let signature = source
+                .signed
+                .then(|| fetch_signature(&format!("{}.{}", url, SIGNATURE_EXTENSION)));
+            // A document that doesn't verify never replaces the last good copy
+            let verify = |body: &str, document_signature: Option<&str>| {
+                validate_registry_body(body)?;
+                verify_document(source, body, || match (document_signature, &signature) {
+                    (Some(document_signature), _) => Ok(document_signature.to_string()),
+                    (None, Some(Err(e))) => Err(e.clone()),
+                    (None, _) => Err("No signature was stored with the document".to_string()),
+                })
+            };
+            let fetched_signature = signature.as_ref().and_then(|s| s.as_deref().ok());
+            let (body, signer, info) =
+                fetch_document_with_cache(&url, &cache_dir, fetched_signature, verify)?;
+
+            let mut entries = serde_json::from_str::<Value>(&body)
+                .map_err(|e| format!("Failed to parse app registry JSON: {}", e))?
+                .as_array()
+                .cloned()
+                .ok_or("App registry is not an array")?;
+            tag_signer(&mut entries, signer.as_deref());
+
+            Ok(SourceDocument {
+                entries,
+                source: info.source,
+                fetched_at: info.fetched_at,
+                error: info.error,
+                signers: signer.into_iter().collect(),
+            })
+        }
+        SourceLocation::File(path) => {
+            let (entries, signer) = read_registry_file(&path, source)?;
+            Ok(SourceDocument {
+                entries,
+                source: RegistrySource::File,
+                fetched_at: file_modified_secs(&path),
+                error: None,
+                signers: signer.into_iter().collect(),
+            })
+        }
+        SourceLocation::Directory(dir) => {
+            let (entries, signers) = read_registry_directory(&dir, source)?;
+            Ok(SourceDocument {
+                entries,
+                source: RegistrySource::File,
+                fetched_at: file_modified_secs(&dir),
+                error: None,
+                signers,
+            })
+        }
+    }
+}
+
+// Merges every source into one registry document. Each app name is taken from the
+// highest priority source that defines it, with ties going to the earlier source.
+pub fn fetch_registry_sources(
+    sources: &[RegistrySourceConfig],
+    cache_root: &Path,
+) -> Result<(Value, Vec<RegistrySourceStatus>), String> {
+    let mut ordered: Vec<&RegistrySourceConfig> = sources.iter().collect();
+    ordered.sort_by_key(|s| std::cmp::Reverse(s.priority));
+
+    let mut merged = Vec::new();
+    let mut statuses = Vec::new();
+    let mut claimed_names = HashSet::new();
+    let mut seen_sources = HashSet::new();
+
+    for source in ordered {
+        let mut status = RegistrySourceStatus {
+            name: source.name.clone(),
+            location: source.location.clone(),
+            priority: source.priority,
+            app_count: 0,
+            source: None,
+            fetched_at: None,
+            signers: Vec::new(),
+            error: None,
+        };
+
+        if !seen_sources.insert(source.name.clone()) {
+            status.error = Some(format!("Duplicate registry source name '{}'", source.name));
+            statuses.push(status);
+            continue;
+        }
+
+        match fetch_source(source, cache_root) {
+            Ok(document) => {
+                let mut source_names = HashSet::new();
+                for mut entry in document.entries {
+                    if let Some(name) = entry["name"].as_str() {
+                        if claimed_names.contains(name) {
+                            debug!(
+                                "App '{}' from source '{}' is overridden by a higher priority source",
+                                name, source.name
+                            );
+                            continue;
+                        }
+                        // A broken entry is still merged so it gets a warning, but it
+                        // mustn't hide a valid one from a lower priority source
+                        if parse_entry(&entry).is_ok() {
+                            source_names.insert(name.to_string());
+                        }
+                    }
+                    if entry.is_object() {
+                        entry["source"] = json!(source.name);
+                    }
+                    merged.push(entry);
+                    status.app_count += 1;
+                }
+                claimed_names.extend(source_names);
+
+                status.source = Some(document.source);
+                status.fetched_at = Some(document.fetched_at);
+                status.signers = document.signers;
+                status.error = document.error;
+            }
+            Err(e) => {
+                error!("Failed to load registry source '{}': {}", source.name, e);
+                status.error = Some(e);
+            }
+        }
+
+        statuses.push(status);
+    }
+
+    if statuses.iter().all(|s| s.source.is_none()) {
+        let errors: Vec<String> = statuses
+            .iter()
+            .filter_map(|s| s.error.as_ref().map(|e| format!("{}: {}", s.name, e)))
+            .collect();
+        return Err(format!(
+            "Failed to load any registry source: {}",
+            errors.join("; ")
+        ));
+    }
+
+    Ok((Value::Array(merged), statuses))
+}
+
+fn read_cache(cache_dir: &Path, url: &str) -> Option<(String, CacheMetadata)> {
+    let metadata: CacheMetadata = fs::read_to_string(cache_dir.join(CACHE_METADATA_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())?;
+
+    // A cache written for a different URL can't be used to validate this one
+    if metadata.url != url {
+        debug!("Ignoring registry cache for {}", metadata.url);
+        return None;
+    }
+
+    let body = fs::read_to_string(cache_dir.join(CACHE_FILE)).ok()?;
+    Some((body, metadata))
+}
+
+fn write_file_atomically(path: &Path, contents: &str) -> Result<(), String> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn write_cache(
+    cache_dir: &Path,
+    body: Option<&str>,
+    metadata: &CacheMetadata,
+) -> Result<(), String> {
+    fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create registry cache directory: {}", e))?;
+
+    if let Some(body) = body {
+        write_file_atomically(&cache_dir.join(CACHE_FILE), body)?;
+    }
+
+    let metadata_str = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Failed to serialize registry cache metadata: {}", e))?;
+    write_file_atomically(&cache_dir.join(CACHE_METADATA_FILE), &metadata_str)
+}
+
+fn request_registry(url: &str, cached: Option<&CacheMetadata>) -> Result<FetchOutcome, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut request = client.get(url);
+    if let Some(metadata) = cached {
+        if let Some(etag) = &metadata.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &metadata.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request
+        .send()
+        .map_err(|e| format!("Failed to fetch app registry: {}", e))?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(FetchOutcome::NotModified);
+    }
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Failed to fetch app registry: HTTP {}",
+            response.status()
+        ));
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    let body = response
+        .text()
+        .map_err(|e| format!("Failed to read app registry response: {}", e))?;
+
+    Ok(FetchOutcome::Modified {
+        body,
+        etag,
+        last_modified,
+    })
+}
+
+fn validate_registry_body(body: &str) -> Result<(), String> {
+    serde_json::from_str::<Value>(body)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to parse app registry JSON: {}", e))
+}
+
+// Signatures change with every publish and are tiny, so they're fetched once
+// per refresh and only kept alongside the body they verified
+fn fetch_signature(url: &str) -> Result<String, String> {
+    match request_registry(url, None)? {
+        FetchOutcome::Modified { body, .. } => {
+            signature::decode_signature(&body)?;
+            Ok(body)
+        }
+        FetchOutcome::NotModified => Err(format!("Unexpected 304 for {}", url)),
+    }
+}
+
+// Fetches the document at `url`, revalidating the on-disk copy in `cache_dir`
+// and falling back to it when the network is unavailable or the response fails
+// `validate`. Only documents that pass `validate` are written to the cache, together
+// with the `signature` they were checked against, and a cached copy is only ever
+// checked against the signature stored with it.
+fn fetch_document_with_cache<T: Clone>(
+    url: &str,
+    cache_dir: &Path,
+    signature: Option<&str>,
+    validate: impl Fn(&str, Option<&str>) -> Result<T, String>,
+) -> Result<(String, T, RegistryInfo), String> {
+    let cached = read_cache(cache_dir, url).and_then(|(body, metadata)| {
+        match validate(&body, metadata.signature.as_deref()) {
+            Ok(validated) => Some((body, metadata, validated)),
+            Err(e) => {
+                debug!("Ignoring cached copy of {}: {}", url, e);
+                None
+            }
+        }
+    });
+
+    let result = request_registry(url, cached.as_ref().map(|(_, metadata, _)| metadata)).and_then(
+        |outcome| match outcome {
+            FetchOutcome::NotModified => {
+                let (body, mut metadata, validated) = cached
+                    .clone()
+                    .ok_or("Registry server returned 304 without a cached copy")?;
+                info!("{} not modified", url);
+                metadata.fetched_at = now_secs();
+                if let Err(e) = write_cache(cache_dir, None, &metadata) {
+                    warn!("Failed to update registry cache metadata: {}", e);
+                }
+                Ok((body, validated, metadata.fetched_at))
+            }
+            FetchOutcome::Modified {
+                body,
+                etag,
+                last_modified,
+            } => {
+                let validated = validate(&body, signature)?;
+                let metadata = CacheMetadata {
+                    url: url.to_string(),
+                    etag,
+                    last_modified,
+                    fetched_at: now_secs(),
+                    signature: signature.map(str::to_string),
+                };
+                if let Err(e) = write_cache(cache_dir, Some(&body), &metadata) {
+                    warn!("Failed to write registry cache: {}", e);
+                }
+                Ok((body, validated, metadata.fetched_at))
+            }
+        },
+    );
+
+    match result {
+        Ok((body, validated, fetched_at)) => Ok((
+            body,
+            validated,
+            RegistryInfo {
+                url: url.to_string(),
+                source: RegistrySource::Network,
+                fetched_at,
+                error: None,
+            },
+        )),
+        Err(e) => match cached {
+            Some((body, metadata, validated)) => {
+                warn!("{}. Using cached copy from {}", e, cache_dir.display());
+                Ok((
+                    body,
+                    validated,
+                    RegistryInfo {
+                        url: url.to_string(),
+                        source: RegistrySource::Cache,
+                        fetched_at: metadata.fetched_at,
+                        error: Some(e),
+                    },
+                ))
+            }
+            None => {
+                error!("{}", e);
+                Err(e)
+            }
+        },
+    }
+}
+
+// Fetches the registry at `url` through the on-disk cache in `cache_dir`
+pub fn fetch_registry_with_cache(
+    url: &str,
+    cache_dir: &Path,
+) -> Result<(Value, RegistryInfo), String> {
+    let (body, _, info) =
+        fetch_document_with_cache(url, cache_dir, None, |body, _| validate_registry_body(body))?;
+    let registry = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse app registry JSON: {}", e))?;
+    Ok((registry, info))
+}
+
+pub fn set_registry_status(statuses: Vec<RegistrySourceStatus>) {
+    *REGISTRY_STATUS.lock().unwrap() = statuses;
+}
+
+fn validate_app(app: &RegistryApp) -> Result<(), String> {
+    if app.name.trim().is_empty() {
+        return Err("name is empty".to_string());
+    }
+    if app.config.mcp_key.trim().is_empty() {
+        return Err("config.mcpKey is empty".to_string());
+    }
+    // The key names Fleur's checkout and download directories
+    if !crate::binaries::is_plain_file_name(&app.config.mcp_key) {
+        return Err(format!(
+            "config.mcpKey '{}' must be a plain name",
+            app.config.mcp_key
+        ));
+    }
+    if app.config.runtime.trim().is_empty() {
+        return Err("config.runtime is empty".to_string());
+    }
+
+    let platforms = app
+        .config
+        .platforms
+        .iter()
+        .chain(app.config.platform_args.keys());
+    for platform in platforms {
+        if !constraints::PLATFORMS.contains(&platform.as_str()) {
+            return Err(format!("Unknown platform '{}'", platform));
+        }
+    }
+
+    let requires = &app.config.requires;
+    for range in requires.node.iter().chain(requires.python.iter()) {
+        constraints::parse_version_req(range)?;
+    }
+
+    if app.config.runtime == crate::container::CONTAINER_RUNTIME {
+        match &app.config.container {
+            Some(container) if !container.image.trim().is_empty() => {}
+            _ => {
+                return Err("config.container.image is required for the docker runtime".to_string())
+            }
+        }
+    }
+
+    if app.config.runtime == crate::binaries::BINARY_RUNTIME && app.config.downloads.is_empty() {
+        return Err("config.downloads is required for the binary runtime".to_string());
+    }
+    if app.config.runtime == crate::git::GIT_RUNTIME {
+        let git = app
+            .config
+            .git
+            .as_ref()
+            .ok_or("config.git is required for the git runtime")?;
+        crate::git::validate_source(git)?;
+    }
+
+    for (target, download) in &app.config.downloads {
+        match target.split_once('-') {
+            Some((platform, arch))
+                if constraints::PLATFORMS.contains(&platform) && !arch.is_empty() => {}
+            _ => {
+                return Err(format!(
+                    "Unknown download target '{}', expected <platform>-<arch>",
+                    target
+                ))
+            }
+        }
+        if !crate::binaries::is_valid_sha256(&download.sha256) {
+            return Err(format!("Invalid sha256 for download target '{}'", target));
+        }
+    }
+    Ok(())
+}
+
+fn parse_entry(entry: &Value) -> Result<RegistryApp, String> {
+    let app = serde_json::from_value::<RegistryApp>(entry.clone()).map_err(|e| e.to_string())?;
+    validate_app(&app)?;
+    Ok(app)
+}
+
+pub fn parse_registry(
+    registry: &Value,
+) -> Result<(Vec<RegistryApp>, Vec<RegistryWarning>), String> {
+    let entries = registry.as_array().ok_or("App registry is not an array")?;
+
+    let mut apps = Vec::new();
+    let mut warnings = Vec::new();
+    let mut seen_names = HashSet::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let name = entry["name"].as_str().map(|s| s.to_string());
+
+        match parse_entry(entry) {
+            Ok(app) if !seen_names.insert(app.name.clone()) => {
+                warnings.push(RegistryWarning {
+                    index,
+                    name,
+                    message: format!("Duplicate app name '{}', keeping the first entry", app.name),
+                });
+            }
+            Ok(app) => apps.push(app),
+            Err(message) => warnings.push(RegistryWarning {
+                index,
+                name,
+                message,
+            }),
+        }
+    }
+
+    for warning in &warnings {
+        warn!(
+            "Skipping registry entry {} ({}): {}",
+            warning.index,
+            warning.name.as_deref().unwrap_or("unnamed"),
+            warning.message
+        );
+    }
+
+    Ok((apps, warnings))
+}
+
+pub fn load_registry_apps() -> Result<Vec<RegistryApp>, String> {
+    let registry = crate::app::fetch_app_registry()?;
+    let (apps, warnings) = parse_registry(&registry)?;
+
+    debug!(
+        "Loaded {} registry apps with {} warnings",
+        apps.len(),
+        warnings.len()
+    );
+    *REGISTRY_WARNINGS.lock().unwrap() = warnings;
+    Ok(apps)
+}
+
+pub fn diff_registry(previous: &[RegistryApp], current: &[RegistryApp]) -> RegistryDelta {
+    let as_values = |apps: &[RegistryApp]| -> std::collections::BTreeMap<String, Value> {
+        apps.iter()
+            .map(|app| (app.name.clone(), json!(app)))
+            .collect()
+    };
+    let previous = as_values(previous);
+    let current = as_values(current);
+
+    let mut delta = RegistryDelta::default();
+    for (name, app) in &current {
+        match previous.get(name) {
+            None => delta.added.push(name.clone()),
+            Some(old) if old != app => delta.changed.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    delta.removed = previous
+        .keys()
+        .filter(|name| !current.contains_key(*name))
+        .cloned()
+        .collect();
+    delta
+}
+
+// Reloads every source and swaps the result into the memory cache. The delta is
+// empty when there was no previously loaded registry to compare against.
+pub fn refresh_registry() -> Result<RegistryDelta, String> {
+    let registry = crate::app::load_app_registry()?;
+    let (apps, warnings) = parse_registry(&registry)?;
+
+    let previous = crate::app::APP_REGISTRY_CACHE
+        .lock()
+        .unwrap()
+        .replace(registry);
+    *REGISTRY_WARNINGS.lock().unwrap() = warnings;
+
+    let delta = match previous.map(|p| parse_registry(&p)) {
+        Some(Ok((previous_apps, _))) => diff_registry(&previous_apps, &apps),
+        _ => RegistryDelta::default(),
+    };
+    Ok(delta)
+}
+
+// A `registryRefreshInterval` of 0 seconds turns background refreshes off
+pub fn get_refresh_interval() -> Option<Duration> {
+    let secs = settings::get_setting("registryRefreshInterval")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS);
+    if secs == 0 {
+        None
+    } else {
+        Some(Duration::from_secs(secs.max(MIN_REFRESH_INTERVAL_SECS)))
+    }
+}
+
+pub fn start_background_refresh(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        // The interval is read every time so settings changes apply without a restart
+        let interval = get_refresh_interval();
+        std::thread::sleep(interval.unwrap_or(Duration::from_secs(MIN_REFRESH_INTERVAL_SECS)));
+        if interval.is_none() {
+            continue;
+        }
+
+        match refresh_registry() {
+            Ok(delta) if delta.is_empty() => debug!("Background registry refresh found no changes"),
+            Ok(delta) => {
+                info!(
+                    "Registry updated: {} added, {} removed, {} changed",
+                    delta.added.len(),
+                    delta.removed.len(),
+                    delta.changed.len()
+                );
+                if let Err(e) = app.emit(REGISTRY_UPDATED_EVENT, &delta) {
+                    error!("Failed to emit {} event: {}", REGISTRY_UPDATED_EVENT, e);
+                }
+            }
+            Err(e) => warn!("Background registry refresh failed: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_registry_info() -> Result<Value, String> {
+    let now = now_secs();
+    let statuses: Vec<Value> = REGISTRY_STATUS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|status| {
+            let mut value = json!(status);
+            if let Some(fetched_at) = status.fetched_at {
+                value["ageSeconds"] = json!(now.saturating_sub(fetched_at));
+            }
+            value
+        })
+        .collect();
+    Ok(json!(statuses))
+}
+
+#[tauri::command]
+pub fn get_configured_registry_sources() -> Vec<RegistrySourceConfig> {
+    get_registry_sources()
+}
+
+#[tauri::command]
+pub fn set_registry_sources(sources: Vec<RegistrySourceConfig>) -> Result<String, String> {
+    let mut names = HashSet::new();
+    for source in &sources {
+        if source.name.trim().is_empty() {
+            return Err("Registry source name cannot be empty".to_string());
+        }
+        if !names.insert(source.name.clone()) {
+            return Err(format!("Duplicate registry source name '{}'", source.name));
+        }
+        resolve_location(&source.location)?;
+
+        if source.signed && source.public_keys.is_empty() {
+            return Err(format!(
+                "Signed registry source '{}' needs at least one public key",
+                source.name
+            ));
+        }
+        for key in &source.public_keys {
+            if key.name.trim().is_empty() {
+                return Err(format!(
+                    "Public keys for registry source '{}' must be named",
+                    source.name
+                ));
+            }
+            signature::parse_public_key(&key.public_key)?;
+        }
+    }
+
+    let value = serde_json::to_value(&sources)
+        .map_err(|e| format!("Failed to serialize registry sources: {}", e))?;
+    settings::save_setting("registrySources", value)?;
+
+    // Force the next registry load to pick up the new sources
+    *crate::app::APP_REGISTRY_CACHE.lock().unwrap() = None;
+    info!("Saved {} registry sources", sources.len());
+    Ok(format!("Saved {} registry sources", sources.len()))
+}
+
+#[tauri::command]
+pub fn get_registry_warnings() -> Result<Vec<RegistryWarning>, String> {
+    load_registry_apps()?;
+    Ok(REGISTRY_WARNINGS.lock().unwrap().clone())
+}
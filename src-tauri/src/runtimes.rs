@@ -0,0 +1,465 @@
+use crate::binaries::{self, current_target};
+use crate::environment::{report_progress, SetupProgress, SetupStage};
+use lazy_static::lazy_static;
+use log::{debug, info};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RUNTIMES_DIR: &str = "runtimes";
+const DOWNLOAD_PROGRESS_STEP: u64 = 256 * 1024;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PinnedArchive {
+    pub url: String,
+    pub sha256: String,
+}
+
+// Runtime -> version -> target -> archive. Digests are recorded by
+// scripts/update-runtime-checksums, an empty one never installs.
+type Manifest = BTreeMap<String, BTreeMap<String, BTreeMap<String, PinnedArchive>>>;
+
+lazy_static! {
+    static ref MANIFEST: Manifest = serde_json::from_str(include_str!("../runtimes.json"))
+        .expect("runtimes.json is not a valid runtime manifest");
+}
+
+pub fn pinned_archive(
+    runtime: &str,
+    version: &str,
+    target: &str,
+) -> Option<&'static PinnedArchive> {
+    MANIFEST.get(runtime)?.get(version)?.get(target)
+}
+
+// Versions of `runtime` already unpacked in the runtimes directory. Installs
+// are moved into place whole, so every directory there is complete.
+pub fn installed_versions(runtime: &str) -> Vec<semver::Version> {
+    let runtime_root = crate::environment::get_fleur_data_dir()
+        .join(RUNTIMES_DIR)
+        .join(runtime);
+    fs::read_dir(runtime_root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| {
+                    semver::Version::parse(&entry.file_name().to_string_lossy()).ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The pinned release of `runtime` an app's semver range asks for. `default` wins
+// whenever it fits so most apps share one install, otherwise the newest match.
+pub fn select_version(runtime: &str, range: Option<&str>, default: &str) -> Result<String, String> {
+    let range = match range {
+        Some(range) => range,
+        None => return Ok(default.to_string()),
+    };
+    let requirement = crate::constraints::parse_version_req(range)?;
+    let matches = |version: &semver::Version| requirement.matches(version);
+
+    if semver::Version::parse(default).is_ok_and(|version| matches(&version)) {
+        return Ok(default.to_string());
+    }
+    MANIFEST
+        .get(runtime)
+        .into_iter()
+        .flat_map(|versions| versions.keys())
+        .filter_map(|version| semver::Version::parse(version).ok())
+        .filter(matches)
+        .max()
+        .map(|version| version.to_string())
+        .ok_or_else(|| format!("No {} release matching {} is available", runtime, range))
+}
+
+// Which step of a runtime install broke
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstallError {
+    Download {
+        runtime: String,
+        reason: String,
+    },
+    Checksum {
+        runtime: String,
+        expected: String,
+        actual: String,
+    },
+    Extract {
+        runtime: String,
+        reason: String,
+    },
+}
+
+impl InstallError {
+    pub fn stage(&self) -> &'static str {
+        match self {
+            InstallError::Download { .. } => "download",
+            InstallError::Checksum { .. } => "checksum",
+            InstallError::Extract { .. } => "extract",
+        }
+    }
+
+    fn runtime(&self) -> &str {
+        match self {
+            InstallError::Download { runtime, .. }
+            | InstallError::Checksum { runtime, .. }
+            | InstallError::Extract { runtime, .. } => runtime,
+        }
+    }
+}
+
+impl fmt::Display for InstallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Installing {} failed at the {} stage: ",
+            self.runtime(),
+            self.stage()
+        )?;
+        match self {
+            InstallError::Download { reason, .. } | InstallError::Extract { reason, .. } => {
+                write!(f, "{}", reason)
+            }
+            InstallError::Checksum {
+                expected, actual, ..
+            } if expected.is_empty() => {
+                write!(f, "no SHA-256 digest is pinned (archive is {})", actual)
+            }
+            InstallError::Checksum {
+                expected, actual, ..
+            } => write!(f, "expected SHA-256 {}, got {}", expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for InstallError {}
+
+impl From<InstallError> for String {
+    fn from(error: InstallError) -> Self {
+        error.to_string()
+    }
+}
+
+pub fn runtime_dir(runtime: &str, version: &str) -> PathBuf {
+    crate::environment::get_fleur_data_dir()
+        .join(RUNTIMES_DIR)
+        .join(runtime)
+        .join(version)
+}
+
+pub fn executable_name(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// A JavaScript runtime Fleur downloads into its own data directory instead of
+// relying on whatever the user has installed. Its version must be pinned in
+// runtimes.json.
+#[derive(Debug)]
+pub struct ManagedRuntime {
+    pub name: &'static str,
+    pub version: &'static str,
+    // Name of the registry runtime served by the shim
+    pub registry_runtime: &'static str,
+    pub shim_name: &'static str,
+    // Arguments the shim puts in front of the registry args
+    pub shim_args: &'static str,
+}
+
+pub const BUN: ManagedRuntime = ManagedRuntime {
+    name: "bun",
+    version: "1.1.38",
+    registry_runtime: "bunx",
+    shim_name: "bunx-fleur",
+    shim_args: "x",
+};
+
+pub const DENO: ManagedRuntime = ManagedRuntime {
+    name: "deno",
+    version: "2.1.4",
+    registry_runtime: "deno",
+    shim_name: "deno-fleur",
+    shim_args: "",
+};
+
+pub fn managed_runtime(registry_runtime: &str) -> Option<&'static ManagedRuntime> {
+    [&BUN, &DENO]
+        .into_iter()
+        .find(|runtime| runtime.registry_runtime == registry_runtime)
+}
+
+impl ManagedRuntime {
+    pub fn install_dir(&self) -> PathBuf {
+        runtime_dir(self.name, self.version)
+    }
+
+    pub fn executable_path(&self) -> PathBuf {
+        self.install_dir().join(executable_name(self.name))
+    }
+
+    pub fn shim_path(&self) -> PathBuf {
+        let shim_name = if cfg!(target_os = "windows") {
+            format!("{}.cmd", self.shim_name)
+        } else {
+            self.shim_name.to_string()
+        };
+        crate::environment::get_fleur_data_dir()
+            .join("bin")
+            .join(shim_name)
+    }
+
+    pub fn shim_script(&self, executable: &Path) -> String {
+        let shim_args = if self.shim_args.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", self.shim_args)
+        };
+        let title = self.registry_runtime.to_uppercase();
+        let bin_dir = executable.parent().unwrap_or(executable);
+
+        if cfg!(target_os = "windows") {
+            format!(
+                r#"@echo off
+:: {title} shim for Fleur on Windows
+
+set RUNTIME={executable}
+set PATH={bin_dir};%PATH%
+
+"%RUNTIME%" {shim_args}%*
+"#,
+                title = title,
+                executable = executable.display(),
+                bin_dir = bin_dir.display(),
+                shim_args = shim_args
+            )
+        } else {
+            let quote = crate::shell_env::shell_quote;
+            let captured_path = crate::shell_env::captured_path()
+                .map(|path| format!("{}:", quote(&path)))
+                .unwrap_or_default();
+            format!(
+                r#"#!/bin/sh
+# {title} shim for Fleur
+
+RUNTIME="{executable}"
+
+{env}export PATH={bin_dir}:{captured_path}"$PATH"
+
+exec "$RUNTIME" {shim_args}"$@"
+"#,
+                title = title,
+                executable = executable.display(),
+                env = crate::shell_env::shim_exports(),
+                bin_dir = quote(&bin_dir.to_string_lossy()),
+                captured_path = captured_path,
+                shim_args = shim_args
+            )
+        }
+    }
+}
+
+fn extract_archive(archive: &Path, destination: &Path) -> Result<(), String> {
+    // bsdtar ships with macOS and Windows 10+ and reads both tar.gz and zip
+    let output = crate::environment::create_windowless_command("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(destination)
+        .output()
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// Archives either hold the runtime at the top or inside a single folder
+fn archive_root(extracted: &Path) -> PathBuf {
+    let entries: Vec<PathBuf> = fs::read_dir(extracted)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    match entries.as_slice() {
+        [single] if single.is_dir() => single.clone(),
+        _ => extracted.to_path_buf(),
+    }
+}
+
+fn unpack(contents: &[u8], staging_dir: &Path, executable: &Path) -> Result<PathBuf, String> {
+    let _ = fs::remove_dir_all(staging_dir);
+    let extracted = staging_dir.join("contents");
+    fs::create_dir_all(&extracted)
+        .map_err(|e| format!("Failed to create {}: {}", extracted.display(), e))?;
+    let archive = staging_dir.join("archive");
+    fs::write(&archive, contents)
+        .map_err(|e| format!("Failed to write {}: {}", archive.display(), e))?;
+    extract_archive(&archive, &extracted)?;
+
+    let root = archive_root(&extracted);
+    if !root.join(executable).is_file() {
+        return Err(format!(
+            "{} is missing from the archive",
+            executable.display()
+        ));
+    }
+    binaries::mark_executable(&root.join(executable))?;
+    Ok(root)
+}
+
+// Downloads `archive`, checks it against its pinned digest and unpacks it into
+// `install_dir`. The directory only appears once every stage passed.
+pub fn install_archive(
+    runtime: &str,
+    archive: &PinnedArchive,
+    install_dir: &Path,
+    executable: &Path,
+) -> Result<(), InstallError> {
+    install_archive_stages(runtime, archive, install_dir, executable).inspect_err(|error| {
+        report_progress(
+            SetupProgress::new(SetupStage::Failed, error.to_string()).for_runtime(runtime),
+        )
+    })
+}
+
+fn install_archive_stages(
+    runtime: &str,
+    archive: &PinnedArchive,
+    install_dir: &Path,
+    executable: &Path,
+) -> Result<(), InstallError> {
+    report_progress(
+        SetupProgress::new(SetupStage::Downloading, format!("Downloading {}", runtime))
+            .for_runtime(runtime),
+    );
+    let mut reported = 0;
+    let contents = binaries::download_file_with_progress(&archive.url, |downloaded, total| {
+        // Every 256 KiB is plenty for a progress bar
+        if downloaded - reported < DOWNLOAD_PROGRESS_STEP && Some(downloaded) != total {
+            return;
+        }
+        reported = downloaded;
+        report_progress(SetupProgress {
+            downloaded_bytes: Some(downloaded),
+            total_bytes: total,
+            ..SetupProgress::new(SetupStage::Downloading, format!("Downloading {}", runtime))
+                .for_runtime(runtime)
+        });
+    })
+    .map_err(|reason| InstallError::Download {
+        runtime: runtime.to_string(),
+        reason,
+    })?;
+
+    report_progress(
+        SetupProgress::new(SetupStage::Verifying, format!("Verifying {}", runtime))
+            .for_runtime(runtime),
+    );
+    let actual = binaries::sha256_hex(&contents);
+    if archive.sha256.is_empty() || !actual.eq_ignore_ascii_case(&archive.sha256) {
+        return Err(InstallError::Checksum {
+            runtime: runtime.to_string(),
+            expected: archive.sha256.to_lowercase(),
+            actual,
+        });
+    }
+
+    let extract_error = |reason: String| InstallError::Extract {
+        runtime: runtime.to_string(),
+        reason,
+    };
+    let file_name = install_dir
+        .file_name()
+        .ok_or_else(|| extract_error(format!("Invalid install path {}", install_dir.display())))?;
+    let staging_dir =
+        install_dir.with_file_name(format!("{}.staging", file_name.to_string_lossy()));
+
+    report_progress(
+        SetupProgress::new(SetupStage::Extracting, format!("Extracting {}", runtime))
+            .for_runtime(runtime),
+    );
+
+    let result = unpack(&contents, &staging_dir, executable).and_then(|root| {
+        let _ = fs::remove_dir_all(install_dir);
+        fs::rename(&root, install_dir)
+            .map_err(|e| format!("Failed to move {} into place: {}", root.display(), e))
+    });
+    let _ = fs::remove_dir_all(&staging_dir);
+    result.map_err(extract_error)
+}
+
+// Installs a version pinned in runtimes.json unless it's already there,
+// returning its install directory
+pub fn install_pinned(
+    runtime: &str,
+    version: &str,
+    executable: &Path,
+) -> Result<PathBuf, InstallError> {
+    let install_dir = runtime_dir(runtime, version);
+    if install_dir.join(executable).is_file() {
+        debug!(
+            "{} {} already installed at {}",
+            runtime,
+            version,
+            install_dir.display()
+        );
+        return Ok(install_dir);
+    }
+
+    let label = format!("{} {}", runtime, version);
+    let target = current_target();
+    let archive =
+        pinned_archive(runtime, version, &target).ok_or_else(|| InstallError::Download {
+            runtime: label.clone(),
+            reason: format!("No archive is pinned for {}", target),
+        })?;
+
+    info!("Installing {}", label);
+    install_archive(&label, archive, &install_dir, executable)?;
+    info!("Installed {} to {}", label, install_dir.display());
+    Ok(install_dir)
+}
+
+fn install_runtime(runtime: &ManagedRuntime) -> Result<PathBuf, String> {
+    let executable = PathBuf::from(executable_name(runtime.name));
+    let install_dir = install_pinned(runtime.name, runtime.version, &executable)?;
+    Ok(install_dir.join(executable))
+}
+
+// Installs the runtime if needed and (re)writes its shim, returning the shim path
+pub fn ensure_runtime_shim(runtime: &ManagedRuntime) -> Result<String, String> {
+    let shim_path = runtime.shim_path();
+    if crate::environment::is_test_mode() {
+        debug!("Using test mode path for {} shim", runtime.name);
+        return Ok(shim_path.to_string_lossy().to_string());
+    }
+
+    let executable = install_runtime(runtime)?;
+    let script = runtime.shim_script(&executable);
+
+    // Rewritten whenever the pinned version changes
+    if fs::read_to_string(&shim_path).ok().as_deref() != Some(script.as_str()) {
+        if let Some(parent) = shim_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create shim directory: {}", e))?;
+        }
+        fs::write(&shim_path, script).map_err(|e| format!("Failed to write shim script: {}", e))?;
+        crate::binaries::mark_executable(&shim_path)?;
+        info!(
+            "{} shim created at {}",
+            runtime.registry_runtime,
+            shim_path.display()
+        );
+    }
+
+    Ok(shim_path.to_string_lossy().to_string())
+}
@@ -0,0 +1,330 @@
+use crate::clients::ClientType;
+use crate::registry::RegistryApp;
+use lazy_static::lazy_static;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref SEARCH_INDEX: Mutex<Option<(u64, SearchIndex)>> = Mutex::new(None);
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFilters {
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub developers: Vec<String>,
+    // Client whose config decides the installed state of each app
+    #[serde(default)]
+    pub client: Option<String>,
+    #[serde(default)]
+    pub installed: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub app: RegistryApp,
+    pub score: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SearchFacets {
+    pub categories: Vec<FacetCount>,
+    pub developers: Vec<FacetCount>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    // Counted over the apps matching the query and installed filter, so the
+    // frontend can show how many results each facet value would leave
+    pub facets: SearchFacets,
+    pub total: usize,
+}
+
+struct IndexedApp {
+    app: RegistryApp,
+    name: String,
+    name_words: Vec<String>,
+    description: String,
+    description_words: Vec<String>,
+    keywords: Vec<String>,
+}
+
+pub struct SearchIndex {
+    apps: Vec<IndexedApp>,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
+
+// Levenshtein distance that also counts swapping two adjacent characters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+// Longer terms tolerate more typos, short ones must match exactly
+fn allowed_typos(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn is_subsequence(term: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    term.chars().all(|c| chars.any(|t| t == c))
+}
+
+fn fuzzy_word_match(term: &str, words: &[String]) -> bool {
+    let typos = allowed_typos(term);
+    typos > 0
+        && words.iter().any(|word| {
+            // Compare against the word's prefix too so partially typed words still match
+            let prefix: String = word.chars().take(term.chars().count()).collect();
+            edit_distance(term, word) <= typos || edit_distance(term, &prefix) <= typos
+        })
+}
+
+impl IndexedApp {
+    fn new(app: RegistryApp) -> Self {
+        let name = app.name.to_lowercase();
+        let description = app.description.to_lowercase();
+        let mut keywords = words(&app.category.to_lowercase());
+        keywords.extend(words(&app.developer.to_lowercase()));
+        keywords.extend(words(&app.config.mcp_key.to_lowercase()));
+
+        IndexedApp {
+            name_words: words(&name),
+            description_words: words(&description),
+            name,
+            description,
+            keywords,
+            app,
+        }
+    }
+
+    fn score_term(&self, term: &str) -> Option<u32> {
+        if self.name == term {
+            return Some(100);
+        }
+        if self.name.starts_with(term) {
+            return Some(80);
+        }
+        if self.name_words.iter().any(|w| w.starts_with(term)) {
+            return Some(70);
+        }
+        if self.name.contains(term) {
+            return Some(60);
+        }
+        if fuzzy_word_match(term, &self.name_words) {
+            return Some(45);
+        }
+        if term.chars().count() > 2 && is_subsequence(term, &self.name) {
+            return Some(35);
+        }
+        if self.keywords.iter().any(|w| w.starts_with(term)) {
+            return Some(30);
+        }
+        if self.description_words.iter().any(|w| w.starts_with(term)) {
+            return Some(25);
+        }
+        if self.description.contains(term) {
+            return Some(20);
+        }
+        if fuzzy_word_match(term, &self.description_words) || fuzzy_word_match(term, &self.keywords)
+        {
+            return Some(10);
+        }
+        None
+    }
+
+    // Every term of the query has to match somewhere in the app
+    fn score(&self, terms: &[String]) -> Option<u32> {
+        terms.iter().map(|term| self.score_term(term)).sum()
+    }
+}
+
+fn matches_facet(selected: &[String], value: &str) -> bool {
+    selected.is_empty() || selected.iter().any(|s| s.eq_ignore_ascii_case(value))
+}
+
+fn count_facet<'a>(values: impl Iterator<Item = &'a str>) -> Vec<FacetCount> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for value in values.filter(|v| !v.is_empty()) {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut facets: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount {
+            value: value.to_string(),
+            count,
+        })
+        .collect();
+    facets.sort_by_key(|f| std::cmp::Reverse(f.count));
+    facets
+}
+
+impl SearchIndex {
+    pub fn new(apps: &[RegistryApp]) -> Self {
+        SearchIndex {
+            apps: apps.iter().cloned().map(IndexedApp::new).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.apps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty()
+    }
+
+    // `installed_keys` holds the mcpKeys present in the filtered client's config
+    pub fn search(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        installed_keys: Option<&HashSet<String>>,
+    ) -> SearchResults {
+        let terms = words(&query.to_lowercase());
+
+        let matching: Vec<(&IndexedApp, u32, Option<bool>)> = self
+            .apps
+            .iter()
+            .filter_map(|indexed| {
+                let score = indexed.score(&terms)?;
+                let installed =
+                    installed_keys.map(|keys| keys.contains(&indexed.app.config.mcp_key));
+                match (filters.installed, installed) {
+                    (Some(wanted), Some(actual)) if wanted != actual => None,
+                    _ => Some((indexed, score, installed)),
+                }
+            })
+            .collect();
+
+        let facets = SearchFacets {
+            categories: count_facet(matching.iter().map(|(a, _, _)| a.app.category.as_str())),
+            developers: count_facet(matching.iter().map(|(a, _, _)| a.app.developer.as_str())),
+        };
+
+        let mut results: Vec<SearchResult> = matching
+            .into_iter()
+            .filter(|(indexed, _, _)| {
+                matches_facet(&filters.categories, &indexed.app.category)
+                    && matches_facet(&filters.developers, &indexed.app.developer)
+            })
+            .map(|(indexed, score, installed)| SearchResult {
+                app: indexed.app.clone(),
+                score,
+                installed,
+            })
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.app.name.to_lowercase().cmp(&b.app.name.to_lowercase()))
+        });
+
+        SearchResults {
+            total: results.len(),
+            results,
+            facets,
+        }
+    }
+}
+
+fn registry_fingerprint(registry: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    registry.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+// Rebuilds the index whenever the loaded registry document changes
+pub fn index_registry(registry: &Value) -> Result<(), String> {
+    let fingerprint = registry_fingerprint(registry);
+    let mut index = SEARCH_INDEX.lock().unwrap();
+    if index.as_ref().is_some_and(|(f, _)| *f == fingerprint) {
+        return Ok(());
+    }
+
+    let (apps, _) = crate::registry::parse_registry(registry)?;
+    let search_index = SearchIndex::new(&apps);
+    debug!("Built search index with {} apps", search_index.len());
+    *index = Some((fingerprint, search_index));
+    Ok(())
+}
+
+fn installed_keys(client: &str) -> Result<HashSet<String>, String> {
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+    let config = crate::app::get_config(&client_type)?;
+    Ok(config
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .map(|servers| servers.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn search_apps(query: &str, filters: Option<SearchFilters>) -> Result<SearchResults, String> {
+    let filters = filters.unwrap_or_default();
+    debug!("Searching apps for '{}' with filters {:?}", query, filters);
+
+    let registry = crate::app::fetch_app_registry()?;
+    index_registry(&registry)?;
+
+    let installed = match &filters.client {
+        Some(client) => Some(installed_keys(client)?),
+        None if filters.installed.is_some() => {
+            return Err("Filtering by installed state requires a client".to_string())
+        }
+        None => None,
+    };
+
+    let index = SEARCH_INDEX.lock().unwrap();
+    let (_, search_index) = index.as_ref().ok_or("Search index is not available")?;
+    Ok(search_index.search(query, &filters, installed.as_ref()))
+}
@@ -0,0 +1,111 @@
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref TEST_SETTINGS_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+pub fn set_test_settings_path(path: Option<PathBuf>) {
+    let mut test_path = TEST_SETTINGS_PATH.lock().unwrap();
+    *test_path = path;
+}
+
+fn get_settings_path() -> Result<Option<PathBuf>, String> {
+    if let Some(path) = TEST_SETTINGS_PATH.lock().unwrap().clone() {
+        return Ok(Some(path));
+    }
+
+    // Never touch the real settings file from tests
+    if crate::environment::is_test_mode() {
+        return Ok(None);
+    }
+
+    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+
+    #[cfg(target_os = "macos")]
+    let settings_file = home.join(".fleur/settings.json");
+
+    #[cfg(target_os = "windows")]
+    let settings_file = home.join(".fleur").join("settings.json");
+
+    Ok(Some(settings_file))
+}
+
+pub fn load_settings() -> Result<Value, String> {
+    let settings_path = match get_settings_path()? {
+        Some(path) => path,
+        None => return Ok(json!({})),
+    };
+
+    if !settings_path.exists() {
+        return Ok(json!({}));
+    }
+
+    let settings_str = fs::read_to_string(&settings_path).map_err(|e| {
+        error!("Failed to read settings file: {}", e);
+        format!("Failed to read settings file: {}", e)
+    })?;
+
+    let settings: Value = serde_json::from_str(&settings_str).map_err(|e| {
+        error!("Failed to parse settings JSON: {}", e);
+        format!("Failed to parse settings JSON: {}", e)
+    })?;
+
+    if !settings.is_object() {
+        return Err("Settings file is not a JSON object".to_string());
+    }
+
+    Ok(settings)
+}
+
+pub fn get_setting(key: &str) -> Option<Value> {
+    match load_settings() {
+        Ok(settings) => settings.get(key).cloned().filter(|v| !v.is_null()),
+        Err(e) => {
+            error!("Failed to load settings, ignoring '{}': {}", key, e);
+            None
+        }
+    }
+}
+
+pub fn save_setting(key: &str, value: Value) -> Result<Value, String> {
+    let settings_path = get_settings_path()?.ok_or("No settings path available")?;
+    let mut settings = load_settings()?;
+
+    if value.is_null() {
+        settings.as_object_mut().unwrap().remove(key);
+    } else {
+        settings[key] = value;
+    }
+
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    let settings_str = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&settings_path, settings_str).map_err(|e| {
+        error!("Failed to write settings file: {}", e);
+        format!("Failed to write settings file: {}", e)
+    })?;
+
+    debug!("Saved setting '{}' to {}", key, settings_path.display());
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_settings() -> Result<Value, String> {
+    load_settings()
+}
+
+#[tauri::command]
+pub fn update_setting(key: &str, value: Value) -> Result<Value, String> {
+    info!("Updating setting: {}", key);
+    save_setting(key, value)
+}
@@ -0,0 +1,232 @@
+use crate::settings::{get_setting, save_setting};
+use log::{debug, info, warn};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Captured values live here, an absent or empty object means the feature is off
+const SHELL_ENVIRONMENT_SETTING: &str = "shellEnvironment";
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(15);
+// Printed before `env` so whatever the shell's rc files echo can be skipped
+const ENV_MARKER: &str = "__FLEUR_SHELL_ENV__";
+
+pub const DEFAULT_VARIABLES: &[&str] = &["PATH"];
+
+// Parses the NUL separated `env -0` output that follows ENV_MARKER
+pub fn parse_env_output(output: &[u8]) -> BTreeMap<String, String> {
+    let output = String::from_utf8_lossy(output);
+    let Some((_, env)) = output.split_once(&format!("{}\0", ENV_MARKER)) else {
+        return BTreeMap::new();
+    };
+
+    env.split('\0')
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn login_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/zsh".to_string())
+}
+
+// Runs `shell` as an interactive login shell, the way a terminal would, and
+// returns the environment it ends up with
+pub fn capture_login_environment(shell: &str) -> Result<BTreeMap<String, String>, String> {
+    info!("Capturing the login environment of {}", shell);
+    let mut child = std::process::Command::new(shell)
+        .args([
+            "-l",
+            "-i",
+            "-c",
+            &format!("printf '{}\\0'; env -0", ENV_MARKER),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", shell, e))?;
+
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to read the shell output")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = stdout.read_to_end(&mut output);
+        let _ = tx.send(output);
+    });
+
+    // Interactive shells can wait on a prompt or a slow rc file forever
+    let output = rx.recv_timeout(CAPTURE_TIMEOUT);
+    let _ = child.kill();
+    let _ = child.wait();
+    let output =
+        output.map_err(|_| format!("{} didn't finish within {:?}", shell, CAPTURE_TIMEOUT))?;
+
+    let env = parse_env_output(&output);
+    if env.is_empty() {
+        return Err(format!("{} didn't print its environment", shell));
+    }
+    Ok(env)
+}
+
+pub fn select_variables(
+    env: &BTreeMap<String, String>,
+    variables: &[String],
+) -> BTreeMap<String, String> {
+    variables
+        .iter()
+        .filter_map(|name| env.get(name).map(|value| (name.clone(), value.clone())))
+        .collect()
+}
+
+// Variables captured earlier, empty unless the user opted in
+pub fn captured_environment() -> BTreeMap<String, String> {
+    get_setting(SHELL_ENVIRONMENT_SETTING)
+        .and_then(|value| match serde_json::from_value(value) {
+            Ok(env) => Some(env),
+            Err(e) => {
+                warn!(
+                    "Ignoring invalid {} setting: {}",
+                    SHELL_ENVIRONMENT_SETTING, e
+                );
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+// Quotes a value for the sh shims, a `'` inside becomes `'\''`
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn is_shell_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// The captured PATH, each shim decides where it goes in its own PATH
+pub fn captured_path() -> Option<String> {
+    captured_environment().remove("PATH")
+}
+
+// Export lines for the other captured variables. Whatever the client sets for
+// a server is already in the environment and wins.
+pub fn shim_exports() -> String {
+    captured_environment()
+        .into_iter()
+        .filter(|(name, _)| name != "PATH")
+        .filter(|(name, _)| {
+            let valid = is_shell_name(name);
+            if !valid {
+                warn!("Not exporting {:?}, it isn't a valid variable name", name);
+            }
+            valid
+        })
+        .map(|(name, value)| {
+            format!(
+                "[ -n \"${{{name}+set}}\" ] || export {name}={}\n",
+                shell_quote(&value),
+                name = name
+            )
+        })
+        .collect()
+}
+
+// Adds the captured variables an entry's env doesn't set itself, for apps that
+// don't run through a shim
+pub fn apply_to(env: &mut Map<String, Value>, captured: &BTreeMap<String, String>) {
+    for (name, value) in captured {
+        env.entry(name.clone())
+            .or_insert_with(|| Value::String(value.clone()));
+    }
+}
+
+// Takes out what `apply_to` added, values the user changed since are kept
+pub fn remove_from(env: &mut Map<String, Value>, captured: &BTreeMap<String, String>) {
+    env.retain(|name, value| match captured.get(name) {
+        Some(captured) => value.as_str() != Some(captured.as_str()),
+        None => true,
+    });
+}
+
+pub fn store_environment(env: &BTreeMap<String, String>) -> Result<(), String> {
+    let previous = captured_environment();
+    let value = if env.is_empty() {
+        Value::Null
+    } else {
+        serde_json::to_value(env).map_err(|e| format!("Failed to serialize environment: {}", e))?
+    };
+    save_setting(SHELL_ENVIRONMENT_SETTING, value)?;
+
+    // Apps get the captured variables through the shims they run with, or
+    // through their env when they have no shim
+    crate::environment::rewrite_shims();
+    match crate::app::reapply_shell_environment(&previous, env) {
+        Ok(0) => {}
+        Ok(count) => info!("Updated the shell environment of {} client entries", count),
+        Err(e) => warn!("Failed to update client entries: {}", e),
+    }
+    Ok(())
+}
+
+fn capture_and_store(variables: Option<Vec<String>>) -> Result<BTreeMap<String, String>, String> {
+    if cfg!(target_os = "windows") {
+        return Err("Apps launched on Windows already get the user's environment".to_string());
+    }
+
+    let variables = variables.unwrap_or_else(|| {
+        DEFAULT_VARIABLES
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    });
+    let env = capture_login_environment(&login_shell())?;
+    let selected = select_variables(&env, &variables);
+    debug!(
+        "Captured {:?} from the login shell",
+        selected.keys().collect::<Vec<_>>()
+    );
+    store_environment(&selected)?;
+    Ok(selected)
+}
+
+#[tauri::command]
+pub async fn capture_shell_environment(
+    variables: Option<Vec<String>>,
+) -> Result<BTreeMap<String, String>, String> {
+    // Login shells can take seconds to start, keep them off the main thread
+    tauri::async_runtime::spawn_blocking(move || capture_and_store(variables))
+        .await
+        .map_err(|e| format!("Capturing the shell environment failed unexpectedly: {}", e))?
+}
+
+#[tauri::command]
+pub fn get_shell_environment() -> BTreeMap<String, String> {
+    captured_environment()
+}
+
+pub fn clear_environment() -> Result<(), String> {
+    info!("Clearing the captured shell environment");
+    store_environment(&BTreeMap::new())
+}
+
+#[tauri::command]
+pub async fn clear_shell_environment() -> Result<(), String> {
+    // Rewrites shims and client entries, which can take a moment
+    tauri::async_runtime::spawn_blocking(clear_environment)
+        .await
+        .map_err(|e| format!("Clearing the shell environment failed unexpectedly: {}", e))?
+}
@@ -0,0 +1,45 @@
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedKey {
+    // Shown as the signer of every app verified with this key
+    pub name: String,
+    // Minisign public key, either the base64 line or the whole minisign.pub file
+    pub public_key: String,
+}
+
+pub fn parse_public_key(key: &str) -> Result<PublicKey, String> {
+    let key = key.trim();
+    let encoded = key.lines().last().unwrap_or(key).trim();
+    PublicKey::from_base64(encoded).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+pub fn decode_signature(signature: &str) -> Result<Signature, String> {
+    Signature::decode(signature).map_err(|e| format!("Invalid signature: {}", e))
+}
+
+// Verifies a detached minisign signature over `data` and returns the name of
+// the pinned key that produced it.
+pub fn verify_detached(
+    data: &[u8],
+    signature: &str,
+    keys: &[TrustedKey],
+) -> Result<String, String> {
+    if keys.is_empty() {
+        return Err("No public keys are pinned for this source".to_string());
+    }
+
+    let signature = decode_signature(signature)?;
+    for key in keys {
+        let public_key = parse_public_key(&key.public_key)?;
+        if public_key.verify(data, &signature, false).is_ok() {
+            return Ok(key.name.clone());
+        }
+    }
+
+    Err("Signature does not match any pinned public key".to_string())
+}
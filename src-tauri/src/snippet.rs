@@ -0,0 +1,323 @@
+use crate::app::{
+    ensure_managed_runtime, get_config, get_runtime_paths, resolve_runtime_command, save_config,
+};
+use crate::clients::ClientType;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"<([^<>]+)>|\$\{([^}]+)\}").unwrap();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetInput {
+    // Key the value has to be supplied under when installing
+    pub name: String,
+    // The placeholder as written in the snippet, e.g. "<YOUR_TOKEN>"
+    pub placeholder: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetServer {
+    pub key: String,
+    pub command: String,
+    // `command` rewritten to Fleur's managed shim for npx, uvx, bunx and deno
+    pub resolved_command: String,
+    pub args: Vec<String>,
+    pub env: Map<String, Value>,
+    pub inputs: Vec<SnippetInput>,
+}
+
+// Drops commas that directly precede a closing bracket, leaving string literals alone
+fn strip_trailing_commas(json: &str) -> String {
+    let mut cleaned = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            cleaned.push(c);
+            match c {
+                '\\' => cleaned.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if matches!(rest, Some('}') | Some(']')) {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        cleaned.push(c);
+    }
+    cleaned
+}
+
+fn parse_json_leniently(snippet: &str) -> Result<Value, String> {
+    // READMEs often leave trailing commas behind or only show the inner entries
+    let cleaned = strip_trailing_commas(snippet.trim());
+    serde_json::from_str(&cleaned).or_else(|e| {
+        serde_json::from_str(&format!("{{{}}}", cleaned))
+            .map_err(|_| format!("Snippet is not valid JSON: {}", e))
+    })
+}
+
+fn snippet_servers(document: &Value) -> Result<&Map<String, Value>, String> {
+    let object = document
+        .as_object()
+        .ok_or("Snippet must be a JSON object")?;
+
+    for key in ["mcpServers", "servers"] {
+        if let Some(servers) = object.get(key).and_then(|v| v.as_object()) {
+            return Ok(servers);
+        }
+    }
+
+    if !object.is_empty() && object.values().all(|v| v.get("command").is_some()) {
+        return Ok(object);
+    }
+    Err("Snippet doesn't contain an mcpServers object".to_string())
+}
+
+// The name of the runtime a README command refers to, ignoring paths and extensions
+fn runtime_of(command: &str) -> Option<&'static str> {
+    let file_name = command.rsplit(['/', '\\']).next().unwrap_or(command);
+    let stem = file_name
+        .strip_suffix(".cmd")
+        .or_else(|| file_name.strip_suffix(".exe"))
+        .unwrap_or(file_name);
+    match stem {
+        "npx" => Some("npx"),
+        "uvx" => Some("uvx"),
+        "bunx" => Some("bunx"),
+        "deno" => Some("deno"),
+        _ => None,
+    }
+}
+
+fn add_input(inputs: &mut Vec<SnippetInput>, name: &str, placeholder: &str) {
+    if !inputs.iter().any(|input| input.name == name) {
+        inputs.push(SnippetInput {
+            name: name.to_string(),
+            placeholder: placeholder.to_string(),
+        });
+    }
+}
+
+fn placeholder_name(captures: &regex::Captures) -> String {
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map(|m| m.as_str().trim().to_string())
+        .unwrap_or_default()
+}
+
+fn detect_inputs(args: &[String], env: &Map<String, Value>) -> Vec<SnippetInput> {
+    let mut inputs = Vec::new();
+
+    // Env values are filled in under their variable name
+    for (key, value) in env {
+        if let Some(text) = value.as_str() {
+            if let Some(m) = PLACEHOLDER.find(text) {
+                add_input(&mut inputs, key, m.as_str());
+            }
+        }
+    }
+
+    for arg in args {
+        for captures in PLACEHOLDER.captures_iter(arg) {
+            let name = placeholder_name(&captures);
+            let is_env_reference = captures.get(2).is_some()
+                && env
+                    .get(&name)
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|v| !PLACEHOLDER.is_match(v));
+            if !name.is_empty() && !is_env_reference {
+                add_input(&mut inputs, &name, &captures[0]);
+            }
+        }
+    }
+
+    inputs
+}
+
+pub fn parse_snippet_with_paths(
+    snippet: &str,
+    npx_shim: &str,
+    uvx_path: &str,
+) -> Result<Vec<SnippetServer>, String> {
+    let document = parse_json_leniently(snippet)?;
+    let servers = snippet_servers(&document)?;
+
+    let mut parsed = Vec::new();
+    for (key, server) in servers {
+        let command = server
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "Server '{}' has no command, only stdio servers are supported",
+                    key
+                )
+            })?
+            .to_string();
+        let args: Vec<String> = match server.get("args") {
+            None => Vec::new(),
+            Some(args) => serde_json::from_value(args.clone())
+                .map_err(|_| format!("Server '{}' args must be a list of strings", key))?,
+        };
+        let env = server
+            .get("env")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        let resolved_command = match runtime_of(&command) {
+            Some(runtime) => resolve_runtime_command(runtime, npx_shim, uvx_path),
+            None => command.clone(),
+        };
+
+        parsed.push(SnippetServer {
+            key: key.clone(),
+            inputs: detect_inputs(&args, &env),
+            command,
+            resolved_command,
+            args,
+            env,
+        });
+    }
+
+    if parsed.is_empty() {
+        return Err("Snippet doesn't define any servers".to_string());
+    }
+    Ok(parsed)
+}
+
+fn fill_placeholders(text: &str, values: &Map<String, Value>, env: &Map<String, Value>) -> String {
+    PLACEHOLDER
+        .replace_all(text, |captures: &regex::Captures| {
+            let name = placeholder_name(captures);
+            values
+                .get(&name)
+                .or_else(|| env.get(&name))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+// Builds the client config entry once every input has a value
+pub fn build_server_config(
+    server: &SnippetServer,
+    values: &Map<String, Value>,
+) -> Result<Value, String> {
+    let missing: Vec<&str> = server
+        .inputs
+        .iter()
+        .filter(|input| {
+            values
+                .get(&input.name)
+                .and_then(|v| v.as_str())
+                .is_none_or(|v| v.trim().is_empty())
+        })
+        .map(|input| input.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing values for {}: {}",
+            server.key,
+            missing.join(", ")
+        ));
+    }
+
+    let mut env = Map::new();
+    for (key, value) in &server.env {
+        let filled = match value.as_str() {
+            Some(_) if values.contains_key(key) => values[key].clone(),
+            Some(text) => json!(fill_placeholders(text, values, &server.env)),
+            None => value.clone(),
+        };
+        env.insert(key.clone(), filled);
+    }
+
+    let args: Vec<String> = server
+        .args
+        .iter()
+        .map(|arg| fill_placeholders(arg, values, &env))
+        .collect();
+
+    Ok(json!({
+        "command": server.resolved_command,
+        "args": args,
+        "env": env
+    }))
+}
+
+#[tauri::command]
+pub fn parse_mcp_snippet(snippet: &str) -> Result<Vec<SnippetServer>, String> {
+    let (npx_shim, uvx_path) = get_runtime_paths()?;
+    parse_snippet_with_paths(snippet, &npx_shim, &uvx_path)
+}
+
+#[tauri::command]
+pub fn install_mcp_snippet(
+    snippet: &str,
+    values: Option<Value>,
+    client: &str,
+) -> Result<String, String> {
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+    let values = values
+        .and_then(|v| v.as_object().cloned())
+        .unwrap_or_default();
+
+    let servers = parse_mcp_snippet(snippet)?;
+    // Build every entry first so a missing value doesn't leave a half-installed snippet
+    let entries = servers
+        .iter()
+        .map(|server| build_server_config(server, &values).map(|entry| (server, entry)))
+        .collect::<Result<Vec<_>, String>>()?;
+    for server in &servers {
+        if let Some(runtime) = runtime_of(&server.command) {
+            ensure_managed_runtime(runtime)?;
+        }
+    }
+
+    let mut config_json = get_config(&client_type)?;
+    let mcp_servers = config_json
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Failed to find mcpServers in config")?;
+
+    let mut keys = Vec::new();
+    for (server, entry) in entries {
+        if mcp_servers.insert(server.key.clone(), entry).is_some() {
+            warn!(
+                "Replaced existing {} configuration from snippet",
+                server.key
+            );
+        }
+        keys.push(server.key.clone());
+    }
+    save_config(&config_json, &client_type)?;
+
+    info!(
+        "Installed {} from snippet for client: {}",
+        keys.join(", "),
+        client_type.as_str()
+    );
+    Ok(format!("Added {} configuration", keys.join(", ")))
+}
@@ -0,0 +1,512 @@
+use crate::app::{get_app_configs, get_config, replace_env_vars};
+use crate::clients::ClientType;
+use crate::environment::create_windowless_command;
+use crate::settings;
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// First runs of npx/uvx servers download packages, so be generous
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+lazy_static! {
+    static ref TOOL_CACHE: Mutex<HashMap<String, ServerTools>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTools {
+    pub tool_count: usize,
+    pub estimated_tokens: usize,
+    pub tools: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerToolUsage {
+    pub mcp_key: String,
+    pub app_name: Option<String>,
+    pub tool_count: usize,
+    pub estimated_tokens: usize,
+    pub tools: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolBudget {
+    pub max_tools: Option<usize>,
+    pub max_tokens: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetWarningKind {
+    ClientLimit,
+    ToolBudget,
+    TokenBudget,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetWarning {
+    pub kind: BudgetWarningKind,
+    pub message: String,
+}
+
+pub fn load_tool_budget() -> ToolBudget {
+    settings::get_setting("toolBudget")
+        .and_then(|value| match serde_json::from_value(value) {
+            Ok(budget) => Some(budget),
+            Err(e) => {
+                warn!("Ignoring invalid toolBudget setting: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+// Rough heuristic: one token per four characters of serialized schema
+pub fn estimate_tool_tokens(tool: &Value) -> usize {
+    serde_json::to_string(tool)
+        .map(|s| s.chars().count().div_ceil(4))
+        .unwrap_or(0)
+}
+
+pub fn budget_warnings(
+    client: &ClientType,
+    budget: &ToolBudget,
+    total_tools: usize,
+    total_tokens: usize,
+) -> Vec<BudgetWarning> {
+    let mut warnings = Vec::new();
+
+    if let Some(limit) = client.tool_limit() {
+        if total_tools > limit {
+            warnings.push(BudgetWarning {
+                kind: BudgetWarningKind::ClientLimit,
+                message: format!(
+                    "{} tools exceed the {} limit of {} tools",
+                    total_tools,
+                    client.as_str(),
+                    limit
+                ),
+            });
+        }
+    }
+
+    if let Some(max_tools) = budget.max_tools {
+        if total_tools > max_tools {
+            warnings.push(BudgetWarning {
+                kind: BudgetWarningKind::ToolBudget,
+                message: format!(
+                    "{} tools exceed the configured budget of {} tools",
+                    total_tools, max_tools
+                ),
+            });
+        }
+    }
+
+    if let Some(max_tokens) = budget.max_tokens {
+        if total_tokens > max_tokens {
+            warnings.push(BudgetWarning {
+                kind: BudgetWarningKind::TokenBudget,
+                message: format!(
+                    "~{} tokens of tool schemas exceed the configured budget of {} tokens",
+                    total_tokens, max_tokens
+                ),
+            });
+        }
+    }
+
+    warnings
+}
+
+fn send_message(stdin: &mut ChildStdin, message: &Value) -> Result<(), String> {
+    let line = serde_json::to_string(message)
+        .map_err(|e| format!("Failed to serialize MCP message: {}", e))?;
+    writeln!(stdin, "{}", line)
+        .and_then(|_| stdin.flush())
+        .map_err(|e| format!("Failed to write to MCP server: {}", e))
+}
+
+fn wait_for_response(rx: &Receiver<String>, id: u64, deadline: Instant) -> Result<Value, String> {
+    loop {
+        let remaining = deadline
+            .checked_duration_since(Instant::now())
+            .ok_or("Timed out waiting for MCP server response")?;
+
+        let line = rx.recv_timeout(remaining).map_err(|e| match e {
+            RecvTimeoutError::Timeout => "Timed out waiting for MCP server response".to_string(),
+            RecvTimeoutError::Disconnected => "MCP server exited before responding".to_string(),
+        })?;
+
+        let message: Value = match serde_json::from_str(line.trim()) {
+            Ok(message) => message,
+            Err(_) => {
+                debug!("Ignoring non-JSON output from MCP server: {}", line);
+                continue;
+            }
+        };
+
+        // Skip notifications and requests initiated by the server
+        if message.get("method").is_some() || message["id"].as_u64() != Some(id) {
+            continue;
+        }
+
+        if let Some(error) = message.get("error") {
+            return Err(format!("MCP server returned an error: {}", error));
+        }
+
+        return Ok(message.get("result").cloned().unwrap_or_else(|| json!({})));
+    }
+}
+
+fn run_tools_handshake(child: &mut Child) -> Result<Vec<Value>, String> {
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or("Failed to open MCP server stdin")?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to open MCP server stdout")?;
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+
+    send_message(
+        &mut stdin,
+        &json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "fleur",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }
+        }),
+    )?;
+    wait_for_response(&rx, 1, deadline)?;
+
+    send_message(
+        &mut stdin,
+        &json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized"
+        }),
+    )?;
+
+    let mut tools = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut id = 2;
+
+    loop {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+
+        send_message(
+            &mut stdin,
+            &json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/list",
+                "params": params
+            }),
+        )?;
+
+        let result = wait_for_response(&rx, id, deadline)?;
+        if let Some(page) = result.get("tools").and_then(|v| v.as_array()) {
+            tools.extend(page.iter().cloned());
+        }
+
+        cursor = result
+            .get("nextCursor")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        if cursor.is_none() {
+            break;
+        }
+        id += 1;
+    }
+
+    Ok(tools)
+}
+
+pub fn list_server_tools(
+    command: &str,
+    args: &[String],
+    env: &Value,
+) -> Result<Vec<Value>, String> {
+    debug!("Starting MCP server to list tools: {} {:?}", command, args);
+
+    let mut cmd = create_windowless_command(command);
+    cmd.args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    if let Some(env) = env.as_object() {
+        for (key, value) in env {
+            match value {
+                Value::String(s) => cmd.env(key, s),
+                other => cmd.env(key, other.to_string()),
+            };
+        }
+    }
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start MCP server '{}': {}", command, e))?;
+
+    let result = run_tools_handshake(&mut child);
+
+    let _ = child.kill();
+    let _ = child.wait();
+
+    result
+}
+
+pub fn probe_server(command: &str, args: &[String], env: &Value) -> Result<ServerTools, String> {
+    let cache_key = json!([command, args, env]).to_string();
+
+    if let Some(cached) = TOOL_CACHE.lock().unwrap().get(&cache_key) {
+        debug!("Using cached tool list for {}", command);
+        return Ok(cached.clone());
+    }
+
+    let tools = list_server_tools(command, args, env)?;
+    let server_tools = ServerTools {
+        tool_count: tools.len(),
+        estimated_tokens: tools.iter().map(estimate_tool_tokens).sum(),
+        tools: tools
+            .iter()
+            .filter_map(|tool| tool["name"].as_str().map(|s| s.to_string()))
+            .collect(),
+    };
+
+    TOOL_CACHE
+        .lock()
+        .unwrap()
+        .insert(cache_key, server_tools.clone());
+    Ok(server_tools)
+}
+
+pub fn clear_tool_cache() {
+    TOOL_CACHE.lock().unwrap().clear();
+}
+
+fn registry_names_by_key() -> HashMap<String, String> {
+    match get_app_configs() {
+        Ok(configs) => configs
+            .into_iter()
+            .map(|(name, config)| (config.mcp_key, name))
+            .collect(),
+        Err(e) => {
+            warn!("Could not map MCP keys to app names: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+fn collect_server_usage(client_type: &ClientType) -> Result<Vec<ServerToolUsage>, String> {
+    let config_json = get_config(client_type)?;
+    let names = registry_names_by_key();
+
+    let servers: Vec<(String, Value)> = config_json
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .map(|servers| {
+            servers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let handles: Vec<_> = servers
+        .into_iter()
+        .map(|(mcp_key, server)| {
+            let app_name = names.get(&mcp_key).cloned();
+            std::thread::spawn(move || {
+                let result = match server["command"].as_str() {
+                    Some(command) => {
+                        let args: Vec<String> = server["args"]
+                            .as_array()
+                            .map(|args| {
+                                args.iter()
+                                    .filter_map(|a| a.as_str().map(|s| s.to_string()))
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        let env = server.get("env").cloned().unwrap_or_else(|| json!({}));
+                        probe_server(command, &args, &env)
+                    }
+                    None => Err("Server has no command configured".to_string()),
+                };
+
+                match result {
+                    Ok(tools) => ServerToolUsage {
+                        mcp_key,
+                        app_name,
+                        tool_count: tools.tool_count,
+                        estimated_tokens: tools.estimated_tokens,
+                        tools: tools.tools,
+                        error: None,
+                    },
+                    Err(e) => {
+                        warn!("Failed to list tools for {}: {}", mcp_key, e);
+                        ServerToolUsage {
+                            mcp_key,
+                            app_name,
+                            tool_count: 0,
+                            estimated_tokens: 0,
+                            tools: Vec::new(),
+                            error: Some(e),
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let mut usage: Vec<ServerToolUsage> = handles
+        .into_iter()
+        .filter_map(|handle| handle.join().ok())
+        .collect();
+    usage.sort_by(|a, b| a.mcp_key.cmp(&b.mcp_key));
+    Ok(usage)
+}
+
+pub fn tool_budget_report(client: &str) -> Result<Value, String> {
+    info!("Building tool budget report for client: {}", client);
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let servers = collect_server_usage(&client_type)?;
+    let total_tools: usize = servers.iter().map(|s| s.tool_count).sum();
+    let total_tokens: usize = servers.iter().map(|s| s.estimated_tokens).sum();
+    let budget = load_tool_budget();
+    let warnings = budget_warnings(&client_type, &budget, total_tools, total_tokens);
+
+    Ok(json!({
+        "client": client_type.as_str(),
+        "servers": servers,
+        "totalTools": total_tools,
+        "estimatedTokens": total_tokens,
+        "budget": budget,
+        "clientToolLimit": client_type.tool_limit(),
+        "warnings": warnings
+    }))
+}
+
+pub fn check_install_budget(
+    app_name: &str,
+    env_vars: Option<Value>,
+    client: &str,
+) -> Result<Value, String> {
+    info!(
+        "Checking tool budget for installing {} on client: {}",
+        app_name, client
+    );
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let configs = get_app_configs()?;
+    let (_, config) = configs
+        .iter()
+        .find(|(name, _)| name == app_name)
+        .ok_or_else(|| format!("No configuration available for: {}", app_name))?;
+
+    let existing = collect_server_usage(&client_type)?;
+    let mut env = get_config(&client_type)?["mcpServers"][&config.mcp_key]
+        .get("env")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    if let (Some(env), Some(new_env)) = (
+        env.as_object_mut(),
+        env_vars.as_ref().and_then(|v| v.as_object()),
+    ) {
+        for (k, v) in new_env {
+            env.insert(k.clone(), v.clone());
+        }
+    }
+
+    let args: Vec<String> = config
+        .args
+        .iter()
+        .map(|arg| replace_env_vars(arg, &env))
+        .collect();
+    let candidate = probe_server(&config.command, &args, &env).map_err(|e| {
+        error!("Failed to list tools for {}: {}", app_name, e);
+        format!("Failed to list tools for {}: {}", app_name, e)
+    })?;
+
+    // Reinstalling an app replaces its current tools rather than adding to them
+    let others: Vec<&ServerToolUsage> = existing
+        .iter()
+        .filter(|s| s.mcp_key != config.mcp_key)
+        .collect();
+    let projected_tools = others.iter().map(|s| s.tool_count).sum::<usize>() + candidate.tool_count;
+    let projected_tokens =
+        others.iter().map(|s| s.estimated_tokens).sum::<usize>() + candidate.estimated_tokens;
+
+    let budget = load_tool_budget();
+    let warnings = budget_warnings(&client_type, &budget, projected_tools, projected_tokens);
+
+    Ok(json!({
+        "app": app_name,
+        "client": client_type.as_str(),
+        "toolCount": candidate.tool_count,
+        "estimatedTokens": candidate.estimated_tokens,
+        "tools": candidate.tools,
+        "projectedTools": projected_tools,
+        "projectedTokens": projected_tokens,
+        "budget": budget,
+        "clientToolLimit": client_type.tool_limit(),
+        "withinBudget": warnings.is_empty(),
+        "warnings": warnings
+    }))
+}
+
+#[tauri::command]
+pub async fn get_tool_budget_report(client: String) -> Result<Value, String> {
+    tauri::async_runtime::spawn_blocking(move || tool_budget_report(&client))
+        .await
+        .map_err(|e| format!("Tool budget report failed unexpectedly: {}", e))?
+}
+
+#[tauri::command]
+pub async fn check_tool_budget(
+    app_name: String,
+    env_vars: Option<Value>,
+    client: String,
+) -> Result<Value, String> {
+    tauri::async_runtime::spawn_blocking(move || check_install_budget(&app_name, env_vars, &client))
+        .await
+        .map_err(|e| format!("Tool budget check failed unexpectedly: {}", e))?
+}
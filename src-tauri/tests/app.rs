@@ -85,7 +85,7 @@ fn test_install_and_uninstall() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Test installation
-    let install_result = app::install("Browser", None, ClientType::Claude.as_str());
+    let install_result = app::install_app("Browser", None, ClientType::Claude.as_str());
     assert!(
         install_result.is_ok(),
         "Install failed: {:?}",
@@ -137,7 +137,7 @@ fn test_app_env_operations() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app first
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str()).unwrap();
 
     // Test saving env values
     let env_values = json!({
@@ -193,7 +193,7 @@ fn test_app_statuses() {
     assert!(initial_statuses["configured"].is_object());
 
     // Install an app
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str()).unwrap();
     thread::sleep(Duration::from_millis(100));
 
     // Check updated statuses
@@ -272,7 +272,7 @@ fn test_install_with_env_vars() {
         "TEST_ENV": "test_value",
         "DEBUG": "true"
     });
-    let install_result = app::install(
+    let install_result = app::install_app(
         "Browser",
         Some(env_vars.clone()),
         ClientType::Claude.as_str(),
@@ -320,8 +320,8 @@ fn test_multiple_apps() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install multiple apps
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
-    app::install("Time", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Time", None, ClientType::Claude.as_str()).unwrap();
 
     // Verify both are installed
     assert!(
@@ -409,7 +409,7 @@ fn test_env_var_replacement_during_install() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app
-    app::install("EnvTest", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("EnvTest", None, ClientType::Claude.as_str()).unwrap();
 
     // Get the config directly to verify args
     let config = app::get_config(&ClientType::Claude).unwrap();
@@ -517,7 +517,7 @@ fn test_complex_env_var_replacements() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app
-    app::install("ComplexEnvTest", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("ComplexEnvTest", None, ClientType::Claude.as_str()).unwrap();
 
     // Get the config directly to verify args
     let config = app::get_config(&ClientType::Claude).unwrap();
@@ -559,3 +559,80 @@ fn test_complex_env_var_replacements() {
     cleanup_test_registry();
     environment::set_test_mode(false);
 }
+
+#[test]
+#[serial]
+fn test_outdated_apps_and_update() {
+    environment::set_test_mode(true);
+    setup_test_registry();
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let env_vars = json!({ "TIMEZONE": "Europe/Paris" });
+    app::install_app("Time", Some(env_vars.clone()), ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str()).unwrap();
+
+    // Nothing is outdated right after installing
+    let outdated = app::get_outdated_apps(ClientType::Claude.as_str()).unwrap();
+    assert!(
+        outdated.is_empty(),
+        "Unexpected outdated apps: {:?}",
+        outdated
+    );
+
+    // The registry pins a new version and adds a flag
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        let registry = cache.as_mut().unwrap();
+        registry[1]["config"]["args"] = json!([
+            "-y",
+            "mcp-server-time@2.0.0",
+            "--local-timezone=${TIMEZONE}"
+        ]);
+    }
+
+    let outdated = app::get_outdated_apps(ClientType::Claude.as_str()).unwrap();
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].app_name, "Time");
+    assert_eq!(
+        outdated[0].added_args,
+        vec!["mcp-server-time@2.0.0", "--local-timezone=Europe/Paris"]
+    );
+    assert_eq!(outdated[0].removed_args, vec!["mcp-server-time"]);
+
+    // Updating rewrites the args but keeps the user's env and extra keys
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["mcpServers"]["time"]["disabled"] = json!(false);
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::update_installed_app("Time", ClientType::Claude.as_str()).unwrap();
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let time = &config["mcpServers"]["time"];
+    assert_eq!(
+        time["args"],
+        json!([
+            "-y",
+            "mcp-server-time@2.0.0",
+            "--local-timezone=Europe/Paris"
+        ])
+    );
+    assert_eq!(time["env"], env_vars);
+    assert_eq!(time["disabled"], false);
+    assert!(app::get_outdated_apps(ClientType::Claude.as_str())
+        .unwrap()
+        .is_empty());
+
+    // Apps that aren't installed can't be updated
+    app::uninstall("Browser", ClientType::Claude.as_str()).unwrap();
+    assert!(app::update_installed_app("Browser", ClientType::Claude.as_str()).is_err());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    cleanup_test_registry();
+    environment::set_test_mode(false);
+}
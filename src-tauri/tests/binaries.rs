@@ -0,0 +1,195 @@
+mod common;
+
+use common::TestHttpResponse;
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    binaries,
+    clients::ClientType,
+    environment,
+    registry::{self, BinaryDownload},
+};
+use serde_json::json;
+use serial_test::serial;
+
+const BINARY_CONTENTS: &[u8] = b"#!/bin/sh\necho weather-mcp\n";
+
+#[test]
+fn test_sha256_and_binary_path() {
+    assert_eq!(
+        binaries::sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert!(binaries::is_valid_sha256(&binaries::sha256_hex(b"abc")));
+    assert!(!binaries::is_valid_sha256("not-a-checksum"));
+
+    let download = BinaryDownload {
+        url: "https://github.com/acme/weather/releases/download/v1.2.0/weather-mcp?raw=1"
+            .to_string(),
+        sha256: "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD".to_string(),
+    };
+    let path = binaries::binary_path("weather", &download).unwrap();
+    assert!(path.ends_with("binaries/weather/ba7816bf8f01/weather-mcp"));
+
+    // Keys and file names can't point outside the binaries directory
+    assert!(binaries::binary_path("..", &download).is_err());
+    assert!(binaries::binary_path("../weather", &download).is_err());
+    assert!(binaries::binary_path("a\\b", &download).is_err());
+    let escaping = BinaryDownload {
+        url: "https://example.com/releases/..".to_string(),
+        ..download.clone()
+    };
+    assert!(binaries::binary_path("weather", &escaping).is_err());
+}
+
+#[test]
+fn test_binary_runtime_validation() {
+    let sha256 = binaries::sha256_hex(BINARY_CONTENTS);
+    let (apps, warnings) = registry::parse_registry(&json!([
+        {
+            "name": "No Downloads",
+            "config": { "mcpKey": "none", "runtime": "binary", "args": [] }
+        },
+        {
+            "name": "Bad Target",
+            "config": {
+                "mcpKey": "bad-target",
+                "runtime": "binary",
+                "args": [],
+                "downloads": { "beos-x86": { "url": "https://example.com/a", "sha256": sha256 } }
+            }
+        },
+        {
+            "name": "Bad Checksum",
+            "config": {
+                "mcpKey": "bad-checksum",
+                "runtime": "binary",
+                "args": [],
+                "downloads": { "macos-aarch64": { "url": "https://example.com/a", "sha256": "abc" } }
+            }
+        },
+        {
+            "name": "Weather",
+            "config": {
+                "mcpKey": "weather",
+                "runtime": "binary",
+                "args": [],
+                "downloads": { "macos-aarch64": { "url": "https://example.com/a", "sha256": sha256 } }
+            }
+        }
+    ]))
+    .unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].name, "Weather");
+    assert_eq!(warnings.len(), 3);
+}
+
+#[test]
+#[serial]
+fn test_install_binary_app() {
+    let server = common::start_http_server(|request| match request.path.as_str() {
+        "/weather-mcp" => TestHttpResponse::ok(BINARY_CONTENTS),
+        "/tampered-mcp" => TestHttpResponse::ok(b"something else".to_vec()),
+        _ => TestHttpResponse::status(404),
+    });
+    let sha256 = binaries::sha256_hex(BINARY_CONTENTS);
+    let target = binaries::current_target();
+
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Weather",
+                "config": {
+                    "mcpKey": "weather",
+                    "runtime": "binary",
+                    "args": ["--stdio"],
+                    "downloads": {
+                        (target.clone()): { "url": format!("{}/weather-mcp", server.url), "sha256": sha256 }
+                    }
+                }
+            },
+            {
+                "name": "Tampered",
+                "config": {
+                    "mcpKey": "tampered",
+                    "runtime": "binary",
+                    "args": [],
+                    "downloads": {
+                        (target.clone()): { "url": format!("{}/tampered-mcp", server.url), "sha256": sha256 }
+                    }
+                }
+            },
+            {
+                "name": "Elsewhere",
+                "config": {
+                    "mcpKey": "elsewhere",
+                    "runtime": "binary",
+                    "args": [],
+                    "downloads": {
+                        "linux-riscv64": { "url": format!("{}/weather-mcp", server.url), "sha256": sha256 }
+                    }
+                }
+            }
+        ]));
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let result = app::install_app("Weather", None, ClientType::Claude.as_str());
+    assert!(result.is_ok(), "Install failed: {:?}", result);
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let entry = &config["mcpServers"]["weather"];
+    let binary_path = std::path::PathBuf::from(entry["command"].as_str().unwrap());
+    assert!(binary_path.starts_with(data_dir.path()));
+    assert_eq!(std::fs::read(&binary_path).unwrap(), BINARY_CONTENTS);
+    assert_eq!(entry["args"], json!(["--stdio"]));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&binary_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+
+    // A verified copy is reused instead of downloaded again
+    app::install_app("Weather", None, ClientType::Claude.as_str()).unwrap();
+    assert_eq!(
+        server
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.path == "/weather-mcp")
+            .count(),
+        1
+    );
+
+    // A download that doesn't match its checksum is never installed
+    let result = app::install_app("Tampered", None, ClientType::Claude.as_str());
+    assert!(result.unwrap_err().contains("Checksum mismatch"));
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(config["mcpServers"].get("tampered").is_none());
+    assert!(!data_dir.path().join("binaries/tampered").exists());
+
+    let result = app::install_app("Elsewhere", None, ClientType::Claude.as_str());
+    assert!(result.unwrap_err().contains("No prebuilt binary"));
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_data_dir(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -21,3 +21,123 @@ pub fn setup_test_config() -> (PathBuf, TempDir) {
 
     (config_path, temp_dir)
 }
+
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct TestHttpRequest {
+    pub path: String,
+    pub headers: std::collections::HashMap<String, String>,
+}
+
+#[allow(dead_code)]
+pub struct TestHttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl TestHttpResponse {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        TestHttpResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        TestHttpResponse {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[allow(dead_code)]
+pub struct TestHttpServer {
+    pub url: String,
+    pub requests: std::sync::Arc<std::sync::Mutex<Vec<TestHttpRequest>>>,
+}
+
+// Minimal HTTP/1.1 server so network code can be exercised without leaving the machine
+#[allow(dead_code)]
+pub fn start_http_server<F>(handler: F) -> TestHttpServer
+where
+    F: Fn(&TestHttpRequest) -> TestHttpResponse + Send + Sync + 'static,
+{
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+
+            let mut headers = std::collections::HashMap::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+
+            let request = TestHttpRequest {
+                path: request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or("/")
+                    .to_string(),
+                headers,
+            };
+            recorded.lock().unwrap().push(request.clone());
+
+            let response = handler(&request);
+            let mut head = format!(
+                "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n",
+                response.status,
+                response.body.len()
+            );
+            for (name, value) in &response.headers {
+                head.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            head.push_str("\r\n");
+
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(&response.body);
+            let _ = stream.flush();
+        }
+    });
+
+    TestHttpServer { url, requests }
+}
+
+// A URL nothing is listening on, for simulating being offline
+#[allow(dead_code)]
+pub fn unreachable_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
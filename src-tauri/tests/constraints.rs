@@ -0,0 +1,226 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    constraints::{self, RuntimeVersions},
+    environment, registry,
+};
+use serde_json::json;
+use serial_test::serial;
+
+fn app_config(config: serde_json::Value) -> registry::RegistryAppConfig {
+    let (apps, warnings) =
+        registry::parse_registry(&json!([{ "name": "Test", "config": config }])).unwrap();
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+    apps[0].config.clone()
+}
+
+fn other_platform() -> &'static str {
+    constraints::PLATFORMS
+        .iter()
+        .find(|p| **p != constraints::current_platform())
+        .unwrap()
+}
+
+#[test]
+fn test_parse_version() {
+    assert_eq!(
+        constraints::parse_version("v20.9.0").unwrap().to_string(),
+        "20.9.0"
+    );
+    assert_eq!(
+        constraints::parse_version("3.12").unwrap().to_string(),
+        "3.12.0"
+    );
+    assert_eq!(
+        constraints::parse_version("Python 3.11.4\n")
+            .unwrap()
+            .to_string(),
+        "3.11.4"
+    );
+    assert!(constraints::parse_version("not a version").is_none());
+
+    let range = constraints::parse_version_req(">=20 <23").unwrap();
+    assert_eq!(range, constraints::parse_version_req(">=20, <23").unwrap());
+    assert!(range.matches(&constraints::parse_version("22.12.0").unwrap()));
+    assert!(!range.matches(&constraints::parse_version("23.0.0").unwrap()));
+}
+
+#[test]
+fn test_check_constraints() {
+    let versions = RuntimeVersions {
+        node: constraints::parse_version("20.9.0"),
+        python: constraints::parse_version("3.9.6"),
+    };
+    let always = |_: &str| true;
+
+    let config = app_config(json!({
+        "mcpKey": "notes",
+        "runtime": "npx",
+        "args": ["-y", "notes-mcp"],
+        "platforms": ["macos"],
+        "requires": { "node": ">=18" }
+    }));
+    assert!(constraints::check_constraints(&config, "macos", &versions, always).is_ok());
+    let reason = constraints::check_constraints(&config, "windows", &versions, always).unwrap_err();
+    assert!(reason.contains("macos"), "{}", reason);
+
+    let config = app_config(json!({
+        "mcpKey": "fetch",
+        "runtime": "python3",
+        "args": ["-m", "mcp_server_fetch"],
+        "requires": { "python": ">=3.10" }
+    }));
+    let reason = constraints::check_constraints(&config, "macos", &versions, always).unwrap_err();
+    assert!(reason.contains("3.9.6"), "{}", reason);
+    let no_python = RuntimeVersions {
+        python: None,
+        ..versions.clone()
+    };
+    assert!(constraints::check_constraints(&config, "macos", &no_python, always).is_err());
+
+    // uvx brings its own Python
+    let config = app_config(json!({
+        "mcpKey": "fetch",
+        "runtime": "uvx",
+        "args": ["mcp-server-fetch"],
+        "requires": { "python": ">=3.10" }
+    }));
+    assert!(constraints::check_constraints(&config, "macos", &no_python, always).is_ok());
+
+    let config = app_config(json!({
+        "mcpKey": "docker",
+        "runtime": "npx",
+        "args": [],
+        "requires": { "binaries": ["docker", "git"] }
+    }));
+    let reason =
+        constraints::check_constraints(&config, "linux", &versions, |b| b == "git").unwrap_err();
+    assert_eq!(reason, "Requires docker on the PATH");
+}
+
+#[test]
+fn test_python_request() {
+    for (range, request) in [
+        (">=3.10", ">=3.10"),
+        (">=3.10, <3.13", ">=3.10,<3.13"),
+        ("3.12", ">=3.12,<4"),
+        ("^3.10", ">=3.10,<4"),
+        ("~3.11", ">=3.11,<3.12"),
+        ("=3.12", "==3.12.*"),
+        ("=3.12.1", "==3.12.1"),
+        ("3.*", "==3.*"),
+    ] {
+        assert_eq!(
+            constraints::python_request(range).unwrap(),
+            request,
+            "{}",
+            range
+        );
+    }
+    assert!(constraints::python_request("latest").is_err());
+}
+
+#[test]
+fn test_platform_args_and_validation() {
+    let config = app_config(json!({
+        "mcpKey": "files",
+        "runtime": "npx",
+        "args": ["-y", "files-mcp", "~/Documents"],
+        "platformArgs": { "windows": ["-y", "files-mcp", "%USERPROFILE%\\Documents"] }
+    }));
+    assert_eq!(
+        constraints::resolve_args(&config, "macos")[2],
+        "~/Documents"
+    );
+    assert_eq!(
+        constraints::resolve_args(&config, "windows")[2],
+        "%USERPROFILE%\\Documents"
+    );
+
+    let (apps, warnings) = registry::parse_registry(&json!([
+        { "name": "BadPlatform", "config": { "mcpKey": "a", "runtime": "npx", "args": [], "platforms": ["beos"] } },
+        { "name": "BadRange", "config": { "mcpKey": "b", "runtime": "npx", "args": [], "requires": { "node": "latest" } } }
+    ]))
+    .unwrap();
+    assert!(apps.is_empty());
+    assert_eq!(warnings.len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_unavailable_apps_cannot_be_installed() {
+    environment::set_test_mode(true);
+    let platform = constraints::current_platform();
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Elsewhere",
+                "config": { "mcpKey": "elsewhere", "runtime": "npx", "args": ["-y", "elsewhere"], "platforms": [other_platform()] }
+            },
+            {
+                "name": "Here",
+                "config": {
+                    "mcpKey": "here",
+                    "runtime": "npx",
+                    "args": ["-y", "here"],
+                    "platformArgs": { platform: ["-y", "here", "--native"] }
+                }
+            },
+            {
+                "name": "Node 22",
+                "config": { "mcpKey": "node-22", "runtime": "npx", "args": ["-y", "node-22"], "requires": { "node": ">=22" } }
+            },
+            {
+                "name": "Future Node",
+                "config": { "mcpKey": "future-node", "runtime": "npx", "args": [], "requires": { "node": ">=99" } }
+            },
+            {
+                "name": "Python 3.12",
+                "config": { "mcpKey": "python-312", "runtime": "uvx", "args": ["python-312"], "requires": { "python": ">=3.12" } }
+            }
+        ]));
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path));
+
+    let configs = app::get_app_configs().unwrap();
+    let config = |name: &str| configs.iter().find(|(n, _)| n == name).unwrap().1.clone();
+    assert!(config("Elsewhere").unavailable_reason.is_some());
+    assert!(config("Here").unavailable_reason.is_none());
+    assert_eq!(config("Here").args, vec!["-y", "here", "--native"]);
+    assert_eq!(
+        config("Here").command,
+        "/test/.local/share/fleur/bin/npx-fleur"
+    );
+
+    // Version ranges pick a pinned release with its own shim
+    let node_22 = config("Node 22");
+    assert!(node_22.unavailable_reason.is_none());
+    assert_eq!(node_22.runtime_version.as_deref(), Some("22.12.0"));
+    assert!(node_22.command.ends_with("npx-fleur-22.12.0"));
+    assert!(config("Future Node").unavailable_reason.is_some());
+    let python = config("Python 3.12");
+    assert!(python.unavailable_reason.is_none());
+    assert_eq!(python.args, vec!["--python", ">=3.12", "python-312"]);
+
+    assert!(app::install_app("Elsewhere", None, ClientType::Claude.as_str()).is_err());
+    app::install_app("Here", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Node 22", None, ClientType::Claude.as_str()).unwrap();
+
+    let statuses = app::get_app_statuses(ClientType::Claude.as_str()).unwrap();
+    assert_eq!(statuses["installed"]["Elsewhere"], false);
+    assert!(statuses["unavailable"]["Elsewhere"].is_string());
+    assert!(statuses["unavailable"].get("Here").is_none());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
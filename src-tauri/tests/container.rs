@@ -0,0 +1,137 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    container, environment, registry,
+};
+use serde_json::json;
+use serial_test::serial;
+
+fn container_registry() -> serde_json::Value {
+    json!([{
+        "name": "GitHub",
+        "description": "GitHub API access",
+        "config": {
+            "mcpKey": "github",
+            "runtime": "docker",
+            "args": ["--toolsets", "repos"],
+            "container": {
+                "image": "ghcr.io/github/github-mcp-server",
+                "volumes": ["${WORKSPACE}:/workspace:ro"]
+            }
+        }
+    }])
+}
+
+#[test]
+fn test_build_container_args() {
+    let spec = registry::ContainerSpec {
+        image: "mcp/fetch".to_string(),
+        volumes: vec!["/tmp:/data".to_string()],
+    };
+    let args = container::build_container_args(
+        &spec,
+        &json!({ "API_KEY": "secret", "DEBUG": "1" }),
+        &["--verbose".to_string()],
+    );
+    assert_eq!(
+        args,
+        vec![
+            "run",
+            "-i",
+            "--rm",
+            "-e",
+            "API_KEY",
+            "-e",
+            "DEBUG",
+            "-v",
+            "/tmp:/data",
+            "mcp/fetch",
+            "--verbose"
+        ]
+    );
+}
+
+#[test]
+fn test_docker_runtime_requires_image() {
+    let (apps, warnings) = registry::parse_registry(&json!([
+        {
+            "name": "No Image",
+            "config": { "mcpKey": "no-image", "runtime": "docker", "args": [] }
+        },
+        {
+            "name": "Fetch",
+            "config": {
+                "mcpKey": "fetch",
+                "runtime": "docker",
+                "args": [],
+                "container": { "image": "mcp/fetch" }
+            }
+        }
+    ]))
+    .unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].name, "Fetch");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("container.image"));
+}
+
+#[test]
+#[serial]
+fn test_install_container_app() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(container_registry());
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let result = app::install_app(
+        "GitHub",
+        Some(json!({
+            "GITHUB_PERSONAL_ACCESS_TOKEN": "ghp_test",
+            "WORKSPACE": "/Users/me/code"
+        })),
+        ClientType::Claude.as_str(),
+    );
+    assert!(result.is_ok(), "Install failed: {:?}", result);
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let entry = &config["mcpServers"]["github"];
+    assert_eq!(entry["command"], "/test/bin/docker");
+    assert_eq!(
+        entry["args"],
+        json!([
+            "run",
+            "-i",
+            "--rm",
+            "-e",
+            "GITHUB_PERSONAL_ACCESS_TOKEN",
+            "-e",
+            "WORKSPACE",
+            "-v",
+            "/Users/me/code:/workspace:ro",
+            "ghcr.io/github/github-mcp-server",
+            "--toolsets",
+            "repos"
+        ])
+    );
+    assert_eq!(entry["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_test");
+
+    // The env passthrough doesn't make the installed app look outdated
+    assert!(app::get_outdated_apps(ClientType::Claude.as_str())
+        .unwrap()
+        .is_empty());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
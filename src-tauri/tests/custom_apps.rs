@@ -0,0 +1,173 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    custom_apps::{self, CustomApp},
+    environment,
+};
+use serde_json::json;
+use serial_test::serial;
+
+#[test]
+fn test_custom_app_args() {
+    assert_eq!(custom_apps::mcp_key_for("My Server (dev)"), "my-server-dev");
+
+    let app = CustomApp {
+        name: "Fetch".to_string(),
+        mcp_key: "fetch".to_string(),
+        runtime: "uvx".to_string(),
+        package: "mcp-server-fetch".to_string(),
+        args: vec!["--ignore-robots-txt".to_string()],
+        git: None,
+    };
+    assert_eq!(
+        app.resolved_args(),
+        vec!["mcp-server-fetch", "--ignore-robots-txt"]
+    );
+
+    let binary = CustomApp {
+        runtime: "binary".to_string(),
+        package: "/usr/local/bin/my-mcp".to_string(),
+        ..app
+    };
+    let registry_app = binary.to_registry_app();
+    assert_eq!(registry_app.config.runtime, "/usr/local/bin/my-mcp");
+    assert_eq!(registry_app.config.args, vec!["--ignore-robots-txt"]);
+    assert_eq!(registry_app.source.as_deref(), Some("custom"));
+}
+
+#[test]
+#[serial]
+fn test_install_and_remove_custom_app() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Time",
+            "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] }
+        }]));
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let result = custom_apps::install_custom_app(
+        "Weather Dev",
+        "npx",
+        "@acme/weather-mcp@0.3.0",
+        Some(vec!["--units".to_string(), "${UNITS}".to_string()]),
+        Some(json!({ "UNITS": "metric" })),
+        ClientType::Claude.as_str(),
+        None,
+    );
+    assert!(result.is_ok(), "Custom install failed: {:?}", result);
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let entry = &config["mcpServers"]["weather-dev"];
+    assert_eq!(entry["command"], "/test/.local/share/fleur/bin/npx-fleur");
+    assert_eq!(
+        entry["args"],
+        json!(["-y", "@acme/weather-mcp@0.3.0", "--units", "metric"])
+    );
+    assert_eq!(entry["env"]["UNITS"], "metric");
+
+    // Custom apps are listed and managed like registry apps
+    assert!(app::is_installed("Weather Dev", ClientType::Claude.as_str()).unwrap());
+    let apps = app::get_app_registry().unwrap();
+    let custom = apps
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["name"] == "Weather Dev")
+        .unwrap();
+    assert_eq!(custom["source"], "custom");
+    assert_eq!(custom_apps::get_custom_apps().unwrap().len(), 1);
+
+    // Registry names, unknown runtimes and clashing keys are rejected
+    assert!(custom_apps::install_custom_app(
+        "Time",
+        "npx",
+        "other-time",
+        None,
+        None,
+        ClientType::Claude.as_str(),
+        None
+    )
+    .is_err());
+    assert!(custom_apps::install_custom_app(
+        "Cargo Server",
+        "cargo",
+        "some-crate",
+        None,
+        None,
+        ClientType::Claude.as_str(),
+        None
+    )
+    .is_err());
+    assert!(custom_apps::install_custom_app(
+        "weather dev!",
+        "npx",
+        "other",
+        None,
+        None,
+        ClientType::Claude.as_str(),
+        None
+    )
+    .is_err());
+
+    // Servers the user added by hand are left alone
+    let mut config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["mcpServers"]["hand-written"] = json!({ "command": "/usr/local/bin/mine" });
+    std::fs::write(&config_path, config.to_string()).unwrap();
+    let error = custom_apps::install_custom_app(
+        "Hand Written",
+        "npx",
+        "@acme/other-mcp",
+        None,
+        None,
+        ClientType::Claude.as_str(),
+        None,
+    )
+    .unwrap_err();
+    assert!(error.contains("wasn't added by Fleur"), "{}", error);
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        config["mcpServers"]["hand-written"]["command"],
+        "/usr/local/bin/mine"
+    );
+    assert_eq!(custom_apps::get_custom_apps().unwrap().len(), 1);
+
+    // An install that fails doesn't leave the app saved
+    assert!(custom_apps::install_custom_app(
+        "Notes",
+        "npx",
+        "@acme/notes-mcp",
+        None,
+        None,
+        "NotAClient",
+        None
+    )
+    .is_err());
+    assert_eq!(custom_apps::get_custom_apps().unwrap().len(), 1);
+
+    custom_apps::remove_custom_app("Weather Dev", ClientType::Claude.as_str()).unwrap();
+    assert!(custom_apps::get_custom_apps().unwrap().is_empty());
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(config["mcpServers"].get("weather-dev").is_none());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_data_dir(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::{start_http_server, unreachable_url, TestHttpResponse};
+use fleur_lib::{
+    app,
+    doctor::{self, FixAction},
+    environment,
+};
+use serial_test::serial;
+
+#[test]
+fn test_check_reachable() {
+    let server = start_http_server(|request| match request.path.as_str() {
+        "/missing" => TestHttpResponse::status(404),
+        "/down" => TestHttpResponse::status(503),
+        _ => TestHttpResponse::ok("ok"),
+    });
+
+    assert!(doctor::check_reachable("npm", "npm", &server.url).ok);
+    // Reaching the server is what matters, not what it answers
+    assert!(doctor::check_reachable("npm", "npm", &format!("{}/missing", server.url)).ok);
+
+    let down = doctor::check_reachable("npm", "npm", &format!("{}/down", server.url));
+    assert!(!down.ok);
+    assert!(down.remediation.is_some());
+
+    let unreachable = doctor::check_reachable("pypi", "PyPI", &unreachable_url());
+    assert!(!unreachable.ok);
+    assert!(unreachable.remediation.unwrap().contains("connection"));
+    assert_eq!(unreachable.fix, None);
+}
+
+#[test]
+fn test_is_writable() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let existing = temp_dir.path().join("config.json");
+    std::fs::write(&existing, "{}").unwrap();
+
+    assert!(doctor::is_writable(&existing));
+    assert!(doctor::is_writable(
+        &temp_dir.path().join("a/b/config.json")
+    ));
+    // The probe file doesn't stay behind
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+#[serial]
+fn test_diagnose_environment() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let server = start_http_server(|_| TestHttpResponse::ok("ok"));
+    let endpoints = vec![
+        (
+            "npm".to_string(),
+            "npm registry".to_string(),
+            server.url.clone(),
+        ),
+        ("pypi".to_string(), "PyPI".to_string(), unreachable_url()),
+    ];
+    let report = doctor::diagnose(&endpoints);
+
+    let tools: Vec<&str> = report.tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(tools, vec!["node", "npx", "uv", "uvx"]);
+    assert!(!report.path.is_empty() || std::env::var_os("PATH").is_none());
+
+    let check = |id: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.id == id)
+            .unwrap_or_else(|| panic!("missing check {}", id))
+    };
+
+    // Nothing is installed in the test data dir, so every failure points at setup
+    let node = check("node");
+    assert!(!node.ok);
+    assert_eq!(node.fix, Some(FixAction::SetupEnvironment));
+    let shim = check("npxShim");
+    assert!(!shim.ok);
+    assert_eq!(shim.fix, Some(FixAction::SetupEnvironment));
+    assert!(report.npx_shim.contents.is_none());
+    // uvx is judged by the shim apps run through, not whatever uv is on the system
+    assert!(!check("uvxShim").ok);
+    assert_eq!(check("uvxShim").fix, Some(FixAction::RegenerateUvxShim));
+    assert!(!check("uvx").ok);
+    assert!(report.uvx_shim.contents.is_none());
+
+    assert!(check("npm").ok);
+    assert!(!check("pypi").ok);
+    for failed in report.checks.iter().filter(|check| !check.ok) {
+        assert!(
+            failed.remediation.is_some(),
+            "{} has no remediation",
+            failed.id
+        );
+    }
+
+    let claude = report
+        .client_configs
+        .iter()
+        .find(|config| config.client == "Claude")
+        .unwrap();
+    assert_eq!(
+        claude.path.as_deref(),
+        Some(config_path.to_string_lossy().as_ref())
+    );
+    assert!(claude.exists);
+    assert!(claude.writable);
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_data_dir(None);
+    environment::set_test_mode(false);
+}
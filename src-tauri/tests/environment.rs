@@ -1,17 +1,118 @@
 #[cfg(test)]
 mod common;
 
-use fleur_lib::environment;
+use fleur_lib::{
+    app, constraints,
+    environment::{self, EnvironmentManager, EnvironmentState, ShimHealth},
+    settings,
+};
+use serde_json::json;
+use serial_test::serial;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
 
 #[test]
+#[serial]
 fn test_environment_setup() {
     environment::set_test_mode(true);
-    let result = environment::ensure_environment_sync();
+    let result = environment::ensure_ready();
     assert!(result.is_ok());
     environment::set_test_mode(false);
 }
 
 #[test]
+fn test_environment_manager_states() {
+    let manager = EnvironmentManager::new();
+    assert_eq!(manager.state(), EnvironmentState::Unknown);
+
+    let error = manager
+        .ensure_ready_with(|| Err("uv download failed".to_string()))
+        .unwrap_err();
+    assert_eq!(error, "uv download failed");
+    assert_eq!(
+        manager.state(),
+        EnvironmentState::Failed("uv download failed".to_string())
+    );
+
+    // A failed setup runs again on the next call, a finished one doesn't
+    let runs = AtomicUsize::new(0);
+    let setup = || {
+        runs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    };
+    manager.ensure_ready_with(setup).unwrap();
+    manager.ensure_ready_with(setup).unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(manager.state(), EnvironmentState::Ready);
+
+    let panicking = EnvironmentManager::new();
+    assert!(panicking.ensure_ready_with(|| panic!("boom")).is_err());
+    assert!(matches!(panicking.state(), EnvironmentState::Failed(_)));
+}
+
+// The doctor's fix runs setup again after it succeeded, so a runtime deleted
+// since then gets installed again
+#[test]
+fn test_environment_manager_reset_reruns_setup() {
+    let dir = tempfile::tempdir().unwrap();
+    let node = dir.path().join("node");
+    let setup = || std::fs::write(&node, "").map_err(|e| e.to_string());
+
+    let manager = EnvironmentManager::new();
+    manager.ensure_ready_with(setup).unwrap();
+    std::fs::remove_file(&node).unwrap();
+    manager.ensure_ready_with(setup).unwrap();
+    assert!(!node.exists());
+
+    manager.reset();
+    assert_eq!(manager.state(), EnvironmentState::Unknown);
+    manager.ensure_ready_with(setup).unwrap();
+    assert!(node.exists());
+    assert_eq!(manager.state(), EnvironmentState::Ready);
+}
+
+#[test]
+fn test_environment_manager_waits_for_running_setup() {
+    let manager = Arc::new(EnvironmentManager::new());
+    let (started_tx, started_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+
+    let runner = {
+        let manager = manager.clone();
+        std::thread::spawn(move || {
+            manager.ensure_ready_with(move || {
+                started_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+                Err("Node.js checksum mismatch".to_string())
+            })
+        })
+    };
+    started_rx.recv().unwrap();
+    assert!(manager.state().is_running());
+
+    // Callers arriving mid-run share its outcome instead of starting another
+    let waiter = {
+        let manager = manager.clone();
+        std::thread::spawn(move || manager.ensure_ready_with(|| panic!("ran twice")))
+    };
+    while manager.waiting() == 0 {
+        std::thread::yield_now();
+    }
+    release_tx.send(()).unwrap();
+
+    assert_eq!(
+        runner.join().unwrap().unwrap_err(),
+        "Node.js checksum mismatch"
+    );
+    assert_eq!(
+        waiter.join().unwrap().unwrap_err(),
+        "Node.js checksum mismatch"
+    );
+}
+
+#[test]
+#[serial]
 fn test_node_environment() {
     environment::set_test_mode(true);
     let result = environment::ensure_npx_shim();
@@ -19,3 +120,235 @@ fn test_node_environment() {
     assert!(result.unwrap().contains("npx-fleur"));
     environment::set_test_mode(false);
 }
+
+#[test]
+fn test_parse_shim_targets() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let node = temp_dir.path().join("node");
+    std::fs::write(&node, "").unwrap();
+    let npx = temp_dir.path().join("npx");
+
+    let unix = format!(
+        "#!/bin/sh\nNODE=\"{}\"\nNPX=\"{}\"\nexport NODE_PATH=\"/fleur/lib\"\nexec \"$NODE\" \"$NPX\" \"$@\"\n",
+        node.display(),
+        npx.display()
+    );
+    let targets = environment::parse_shim_targets(&unix);
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0].name, "NODE");
+    assert_eq!(targets[0].path, node.to_string_lossy());
+    assert!(targets[0].exists);
+    assert_eq!(targets[1].name, "NPX");
+    assert!(!targets[1].exists);
+
+    let windows = "@echo off\r\nset NODE=C:\\fleur\\node.exe\r\nset NPX=C:\\fleur\\npx.cmd\r\n\"%NODE%\" \"%NPX%\" %*\r\n";
+    let targets = environment::parse_shim_targets(windows);
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0].path, "C:\\fleur\\node.exe");
+    assert_eq!(targets[1].path, "C:\\fleur\\npx.cmd");
+
+    assert!(environment::parse_shim_targets("garbage").is_empty());
+}
+
+#[test]
+#[serial]
+fn test_repair_stale_npx_shims() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    for version in ["20.9.0", "22.12.0"] {
+        let (node, npx) = environment::get_node_paths_for(version).unwrap();
+        for path in [node, npx] {
+            std::fs::create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        }
+    }
+
+    // The default shim still points at an nvm Node.js that was removed
+    let default_shim = environment::get_npx_shim_path();
+    std::fs::create_dir_all(default_shim.parent().unwrap()).unwrap();
+    std::fs::write(
+        &default_shim,
+        "#!/bin/sh\nNODE=\"/gone/.nvm/node\"\nNPX=\"/gone/.nvm/npx\"\nexec \"$NPX\" \"$@\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        environment::check_shim(&default_shim),
+        ShimHealth::Stale(vec![
+            "/gone/.nvm/node".to_string(),
+            "/gone/.nvm/npx".to_string()
+        ])
+    );
+
+    // A versioned shim from before shims had a format header
+    let (node_22, npx_22) = environment::get_node_paths_for("22.12.0").unwrap();
+    let versioned_shim = default_shim.with_file_name("npx-fleur-22.12.0");
+    std::fs::write(
+        &versioned_shim,
+        format!("#!/bin/sh\nNODE=\"{}\"\nNPX=\"{}\"\n", node_22, npx_22),
+    )
+    .unwrap();
+    assert_eq!(
+        environment::check_shim(&versioned_shim),
+        ShimHealth::OutdatedFormat(None)
+    );
+
+    let mut repaired = environment::repair_npx_shims();
+    repaired.sort();
+    let mut expected = vec![default_shim.clone(), versioned_shim.clone()];
+    expected.sort();
+    assert_eq!(repaired, expected);
+    assert_eq!(environment::check_shim(&default_shim), ShimHealth::Healthy);
+    let contents = std::fs::read_to_string(&versioned_shim).unwrap();
+    assert_eq!(environment::shim_format(&contents), Some(1));
+    assert!(contents.contains(&node_22));
+    assert!(environment::repair_npx_shims().is_empty());
+
+    // Entries launched through the removed npx follow the new shim
+    let shim = default_shim.to_string_lossy().to_string();
+    let versioned = versioned_shim.to_string_lossy().to_string();
+    std::fs::write(
+        &config_path,
+        serde_json::to_string_pretty(&json!({
+            "mcpServers": {
+                "old-npx": { "command": "/gone/.nvm/npx", "args": ["-y", "server"] },
+                "node-22": { "command": versioned, "args": [] },
+                "python": { "command": "uvx", "args": ["mcp-server-time"] }
+            }
+        }))
+        .unwrap(),
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+    assert_eq!(app::repoint_npx_entries().unwrap(), 1);
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["mcpServers"]["old-npx"]["command"], shim);
+    assert_eq!(
+        config["mcpServers"]["old-npx"]["args"],
+        json!(["-y", "server"])
+    );
+    assert_eq!(config["mcpServers"]["node-22"]["command"], versioned);
+    assert_eq!(config["mcpServers"]["python"]["command"], "uvx");
+
+    // A versioned shim that stays broken isn't swapped for the default Node.js
+    std::fs::remove_file(&node_22).unwrap();
+    assert!(matches!(
+        environment::check_shim(&versioned_shim),
+        ShimHealth::Stale(_)
+    ));
+    assert_eq!(app::repoint_npx_entries().unwrap(), 0);
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["mcpServers"]["node-22"]["command"], versioned);
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_data_dir(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_uvx_shim() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+
+    let uv_dir = data_dir.path().join("runtimes").join("uv").join("0.5.11");
+    let uvx = uv_dir.join("uvx");
+    std::fs::create_dir_all(&uv_dir).unwrap();
+    std::fs::write(&uvx, "").unwrap();
+
+    let script = environment::uvx_shim_script(&uvx.to_string_lossy());
+    assert!(script.contains("UV_PYTHON=3.12") || script.contains("UV_PYTHON='3.12'"));
+    assert!(script.contains(
+        &data_dir
+            .path()
+            .join("cache/uv")
+            .to_string_lossy()
+            .to_string()
+    ));
+    assert!(script.contains("only-managed"));
+    assert_eq!(environment::shim_format(&script), Some(1));
+
+    let shim = environment::get_uvx_shim_path();
+    assert!(shim.starts_with(data_dir.path().join("bin")));
+    std::fs::create_dir_all(shim.parent().unwrap()).unwrap();
+    std::fs::write(&shim, &script).unwrap();
+    assert_eq!(environment::check_shim(&shim), ShimHealth::Healthy);
+
+    // uv going away makes the shim stale like an npx shim losing its Node.js
+    std::fs::remove_file(&uvx).unwrap();
+    assert_eq!(
+        environment::check_shim(&shim),
+        ShimHealth::Stale(vec![uvx.to_string_lossy().to_string()])
+    );
+
+    environment::set_test_data_dir(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_node_paths_use_fleur_runtime() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+
+    let node_dir = data_dir.path().join("runtimes").join("node").join("20.9.0");
+    assert_eq!(environment::get_node_dir(), node_dir);
+    let (node, npx) = environment::get_node_paths().unwrap();
+    if cfg!(windows) {
+        assert_eq!(Path::new(&node), node_dir.join("node.exe"));
+        assert_eq!(Path::new(&npx), node_dir.join("npx.cmd"));
+    } else {
+        assert_eq!(Path::new(&node), node_dir.join("bin/node"));
+        assert_eq!(Path::new(&npx), node_dir.join("bin/npx"));
+    }
+
+    // Cleanup
+    environment::set_test_data_dir(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_node_version_policy() {
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    let settings_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(settings_dir.path().join("settings.json")));
+
+    assert_eq!(environment::default_node_version(), "20.9.0");
+    assert_eq!(environment::node_version_for(None).unwrap(), "20.9.0");
+
+    // The minimum moves up through settings
+    settings::save_setting("nodeVersionRange", json!(">=22 <23")).unwrap();
+    assert_eq!(environment::default_node_version(), "22.12.0");
+    assert!(environment::get_node_dir().ends_with("runtimes/node/22.12.0"));
+
+    // A compatible Node.js that is already installed is kept
+    settings::save_setting("nodeVersionRange", json!(">=20 <23")).unwrap();
+    let installed = data_dir.path().join("runtimes/node/22.3.0");
+    std::fs::create_dir_all(&installed).unwrap();
+    std::fs::create_dir_all(data_dir.path().join("runtimes/node/23.0.0")).unwrap();
+    assert_eq!(environment::default_node_version(), "22.3.0");
+
+    // Invalid ranges fall back to the built in policy
+    settings::save_setting("nodeVersionRange", json!("latest")).unwrap();
+    assert_eq!(
+        environment::node_version_range(),
+        constraints::parse_version_req(">=20, <23").unwrap()
+    );
+
+    // Cleanup
+    settings::set_test_settings_path(None);
+    environment::set_test_data_dir(None);
+    environment::set_test_mode(false);
+}
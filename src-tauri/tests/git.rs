@@ -0,0 +1,225 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    custom_apps, environment, git,
+    registry::{self, GitSource},
+};
+use serde_json::json;
+use serial_test::serial;
+use std::path::Path;
+use std::process::Command;
+
+fn run_git(args: &[&str], cwd: &Path) -> String {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Fleur",
+            "-c",
+            "user.email=fleur@example.com",
+        ])
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn commit_file(repo: &Path, name: &str, contents: &str) -> String {
+    std::fs::write(repo.join(name), contents).unwrap();
+    run_git(&["add", "."], repo);
+    run_git(&["commit", "-q", "-m", name], repo);
+    run_git(&["rev-parse", "HEAD"], repo)
+}
+
+fn git_source(url: &str, build: &[&str]) -> GitSource {
+    GitSource {
+        url: url.to_string(),
+        git_ref: Some("main".to_string()),
+        build: build.iter().map(|step| step.to_string()).collect(),
+        entrypoint: "dist/server.js".to_string(),
+        interpreter: None,
+    }
+}
+
+#[test]
+fn test_git_source_validation() {
+    assert!(git::validate_source(&git_source("https://github.com/acme/mcp", &[])).is_ok());
+    assert!(git::validate_source(&git_source("git@github.com:acme/mcp.git", &[])).is_ok());
+    assert!(git::validate_source(&git_source("ssh://git@github.com/acme/mcp", &[])).is_ok());
+    assert!(git::validate_source(&git_source("", &[])).is_err());
+
+    // Anything git could read as an option or a command transport is refused
+    for url in [
+        "--upload-pack=touch /tmp/pwned",
+        "ext::sh -c touch% /tmp/pwned",
+        "file:///etc",
+        "/home/user/repo",
+        "http://github.com/acme/mcp",
+        "git@github.com:--upload-pack=x",
+    ] {
+        assert!(
+            git::validate_source(&git_source(url, &[])).is_err(),
+            "{} was accepted",
+            url
+        );
+    }
+    let option_ref = GitSource {
+        git_ref: Some("--upload-pack=touch /tmp/pwned".to_string()),
+        ..git_source("https://github.com/acme/mcp", &[])
+    };
+    assert!(git::validate_source(&option_ref).is_err());
+
+    let escaping = GitSource {
+        entrypoint: "../outside.js".to_string(),
+        ..git_source("https://github.com/acme/mcp", &[])
+    };
+    assert!(git::validate_source(&escaping).is_err());
+
+    let unknown_interpreter = GitSource {
+        interpreter: Some("ruby".to_string()),
+        ..git_source("https://github.com/acme/mcp", &[])
+    };
+    assert!(git::validate_source(&unknown_interpreter).is_err());
+
+    // Keys never reach the filesystem unless they're a single plain name
+    for key in ["..", "../../Documents", "a/b", ""] {
+        assert!(git::checkout_dir(key).is_err(), "{}", key);
+        assert!(git::build_log_path(key).is_err(), "{}", key);
+    }
+
+    let (apps, warnings) = registry::parse_registry(&json!([{
+        "name": "No Source",
+        "config": { "mcpKey": "no-source", "runtime": "git", "args": [] }
+    }]))
+    .unwrap();
+    assert!(apps.is_empty());
+    assert_eq!(warnings.len(), 1);
+
+    let checkout = Path::new("/fleur/git/weather");
+    let (command, args) = git::resolve_entrypoint(
+        &git_source("https://github.com/acme/mcp", &[]),
+        checkout,
+        &["--stdio".to_string()],
+    )
+    .unwrap();
+    assert_eq!(
+        command,
+        checkout.join("dist/server.js").to_string_lossy().as_ref()
+    );
+    assert_eq!(args, vec!["--stdio"]);
+}
+
+#[test]
+#[serial]
+fn test_install_and_update_git_app() {
+    let upstream = tempfile::tempdir().unwrap();
+    run_git(&["init", "-q", "-b", "main"], upstream.path());
+    let first_commit = commit_file(upstream.path(), "VERSION", "1");
+
+    environment::set_test_mode(true);
+    let data_dir = tempfile::tempdir().unwrap();
+    environment::set_test_data_dir(Some(data_dir.path().to_path_buf()));
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Weather",
+                "config": {
+                    "mcpKey": "weather",
+                    "runtime": "git",
+                    "args": ["--stdio"],
+                    "git": {
+                        "url": upstream.path().to_string_lossy(),
+                        "ref": "main",
+                        "build": [
+                            "git rev-parse HEAD > built.txt",
+                            "echo \"$UV_CACHE_DIR\" > uv_cache.txt",
+                            "echo building"
+                        ],
+                        "entrypoint": "dist/server.js"
+                    }
+                }
+            },
+            {
+                "name": "Broken",
+                "config": {
+                    "mcpKey": "broken",
+                    "runtime": "git",
+                    "args": [],
+                    "git": {
+                        "url": upstream.path().to_string_lossy(),
+                        "build": ["echo compiling && exit 3"],
+                        "entrypoint": "dist/server.js"
+                    }
+                }
+            }
+        ]));
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let result = app::install_app("Weather", None, ClientType::Claude.as_str());
+    assert!(result.is_ok(), "Install failed: {:?}", result);
+
+    let checkout = git::checkout_dir("weather").unwrap();
+    assert!(checkout.starts_with(data_dir.path()));
+    let built = std::fs::read_to_string(checkout.join("built.txt")).unwrap();
+    assert_eq!(built.trim(), first_commit);
+    let build_log = std::fs::read_to_string(git::build_log_path("weather").unwrap()).unwrap();
+    assert!(build_log.contains("building"));
+    // Builds use Fleur's uv setup rather than the user's
+    let uv_cache = std::fs::read_to_string(checkout.join("uv_cache.txt")).unwrap();
+    assert_eq!(Path::new(uv_cache.trim()), data_dir.path().join("cache/uv"));
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let entry = &config["mcpServers"]["weather"];
+    assert_eq!(
+        entry["command"],
+        checkout.join("dist/server.js").to_string_lossy().as_ref()
+    );
+    assert_eq!(entry["args"], json!(["--stdio"]));
+
+    // Updating fetches the new commit and rebuilds
+    let second_commit = commit_file(upstream.path(), "VERSION", "2");
+    app::update_installed_app("Weather", ClientType::Claude.as_str()).unwrap();
+    let built = std::fs::read_to_string(checkout.join("built.txt")).unwrap();
+    assert_eq!(built.trim(), second_commit);
+
+    // Failed builds report their output and leave the client config alone
+    let error = app::install_app("Broken", None, ClientType::Claude.as_str()).unwrap_err();
+    assert!(error.contains("exit 3"), "{}", error);
+    assert!(error.contains("compiling"), "{}", error);
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(config["mcpServers"].get("broken").is_none());
+
+    // Custom installs can point at a repository too
+    let result = custom_apps::install_custom_app(
+        "Local Weather",
+        "git",
+        &upstream.path().to_string_lossy(),
+        None,
+        None,
+        ClientType::Claude.as_str(),
+        Some(git_source("", &["echo custom"])),
+    );
+    assert!(result.is_ok(), "Custom install failed: {:?}", result);
+    assert!(git::checkout_dir("local-weather")
+        .unwrap()
+        .join("VERSION")
+        .exists());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_data_dir(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
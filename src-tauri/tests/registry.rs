@@ -0,0 +1,608 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    environment, registry, settings, signature,
+};
+use serde_json::json;
+use serial_test::serial;
+
+fn malformed_registry() -> serde_json::Value {
+    json!([
+        {
+            "name": "Browser",
+            "description": "Web browser",
+            "icon": {
+                "type": "url",
+                "url": { "light": "browser.svg", "dark": "browser.svg" }
+            },
+            "category": "Utilities",
+            "price": "Free",
+            "developer": "Test Developer",
+            "sourceUrl": "https://example.com/browser",
+            "features": [{ "name": "Browse", "description": "Browse", "prompt": "Open a page" }],
+            "setup": [{ "label": "Token", "type": "input", "key": "TOKEN" }],
+            "envVars": [{ "name": "TOKEN", "label": "Token", "description": "API token" }],
+            "config": {
+                "mcpKey": "puppeteer",
+                "runtime": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-puppeteer"]
+            }
+        },
+        {
+            "name": "NoKey",
+            "description": "Missing mcpKey",
+            "config": { "runtime": "npx", "args": ["-y", "no-key"] }
+        },
+        {
+            "name": "BadArgs",
+            "description": "Non-string args",
+            "config": { "mcpKey": "bad", "runtime": "npx", "args": ["-y", 42] }
+        },
+        {
+            "name": "Escape",
+            "description": "Key that leaves Fleur's data dir",
+            "config": { "mcpKey": "../../Documents", "runtime": "npx", "args": [] }
+        },
+        {
+            "name": "Browser",
+            "description": "Duplicate",
+            "config": { "mcpKey": "dup", "runtime": "npx", "args": [] }
+        }
+    ])
+}
+
+#[test]
+fn test_parse_registry_isolates_invalid_entries() {
+    let (apps, warnings) = registry::parse_registry(&malformed_registry()).unwrap();
+
+    assert_eq!(apps.len(), 1);
+    let browser = &apps[0];
+    assert_eq!(browser.name, "Browser");
+    assert_eq!(browser.config.mcp_key, "puppeteer");
+    assert_eq!(browser.env_vars[0].name, "TOKEN");
+    assert_eq!(browser.setup[0].setup_type, "input");
+
+    assert_eq!(warnings.len(), 4);
+    assert_eq!(warnings[0].name.as_deref(), Some("NoKey"));
+    assert_eq!(warnings[1].name.as_deref(), Some("BadArgs"));
+    assert_eq!(warnings[2].name.as_deref(), Some("Escape"));
+    assert_eq!(warnings[3].index, 4);
+}
+
+#[test]
+fn test_parse_registry_rejects_non_array() {
+    assert!(registry::parse_registry(&json!({ "apps": [] })).is_err());
+}
+
+#[test]
+#[serial]
+fn test_install_with_malformed_entries() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(malformed_registry());
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path));
+
+    let install_result = app::install_app("Browser", None, ClientType::Claude.as_str());
+    assert!(
+        install_result.is_ok(),
+        "Install failed: {:?}",
+        install_result
+    );
+    assert!(app::is_installed("Browser", ClientType::Claude.as_str()).unwrap());
+
+    let statuses = app::get_app_statuses(ClientType::Claude.as_str()).unwrap();
+    assert_eq!(statuses["installed"]["Browser"], true);
+    assert!(statuses["installed"].get("BadArgs").is_none());
+
+    let apps = app::get_app_registry().unwrap();
+    assert_eq!(apps.as_array().unwrap().len(), 1);
+    assert_eq!(apps[0]["sourceUrl"], "https://example.com/browser");
+
+    let warnings = registry::get_registry_warnings().unwrap();
+    assert_eq!(warnings.len(), 4);
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_registry_cache_conditional_fetch_and_offline_fallback() {
+    let registry_body = json!([{
+        "name": "Time",
+        "description": "Time server",
+        "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] }
+    }])
+    .to_string();
+
+    let body = registry_body.clone();
+    let server = common::start_http_server(move |request| {
+        if request.headers.get("if-none-match").map(|s| s.as_str()) == Some("\"v1\"") {
+            common::TestHttpResponse::status(304)
+        } else {
+            common::TestHttpResponse::ok(body.clone())
+                .header("ETag", "\"v1\"")
+                .header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+        }
+    });
+    let url = format!("{}/apps.json", server.url);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    // First fetch downloads and persists the registry
+    let (registry, info) = registry::fetch_registry_with_cache(&url, cache_dir.path()).unwrap();
+    assert_eq!(registry[0]["name"], "Time");
+    assert_eq!(info.source, registry::RegistrySource::Network);
+    assert!(cache_dir.path().join("registry.json").exists());
+
+    // Second fetch revalidates with the stored validators
+    let (registry, info) = registry::fetch_registry_with_cache(&url, cache_dir.path()).unwrap();
+    assert_eq!(registry[0]["name"], "Time");
+    assert_eq!(info.source, registry::RegistrySource::Network);
+    {
+        let requests = server.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].headers["if-none-match"], "\"v1\"");
+        assert_eq!(
+            requests[1].headers["if-modified-since"],
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+    }
+
+    // A cache written for another URL is not reused
+    let offline_url = format!("{}/apps.json", common::unreachable_url());
+    assert!(registry::fetch_registry_with_cache(&offline_url, cache_dir.path()).is_err());
+
+    // Going offline falls back to the cached copy for the same URL
+    let metadata_path = cache_dir.path().join("registry.meta.json");
+    let mut metadata: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&metadata_path).unwrap()).unwrap();
+    metadata["url"] = json!(offline_url);
+    std::fs::write(&metadata_path, metadata.to_string()).unwrap();
+
+    let (registry, info) =
+        registry::fetch_registry_with_cache(&offline_url, cache_dir.path()).unwrap();
+    assert_eq!(registry[0]["name"], "Time");
+    assert_eq!(info.source, registry::RegistrySource::Cache);
+    assert!(info.error.is_some());
+}
+
+#[test]
+fn test_registry_sources_merge_with_precedence() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let internal_file = temp_dir.path().join("internal.json");
+    std::fs::write(
+        &internal_file,
+        json!([{
+            "name": "Browser",
+            "description": "Internal browser build",
+            "config": { "mcpKey": "browser-internal", "runtime": "npx", "args": ["-y", "@corp/browser"] }
+        }])
+        .to_string(),
+    )
+    .unwrap();
+
+    let public_dir = temp_dir.path().join("public");
+    std::fs::create_dir_all(&public_dir).unwrap();
+    std::fs::write(
+        public_dir.join("apps.json"),
+        json!([
+            {
+                "name": "Browser",
+                "description": "Public browser",
+                "config": { "mcpKey": "puppeteer", "runtime": "npx", "args": ["-y", "@modelcontextprotocol/server-puppeteer"] }
+            },
+            {
+                "name": "Time",
+                "description": "Time server",
+                "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] }
+            }
+        ])
+        .to_string(),
+    )
+    .unwrap();
+
+    let sources = vec![
+        registry::RegistrySourceConfig {
+            name: "public".to_string(),
+            location: public_dir.to_string_lossy().to_string(),
+            priority: 0,
+            ..Default::default()
+        },
+        registry::RegistrySourceConfig {
+            name: "internal".to_string(),
+            location: internal_file.to_string_lossy().to_string(),
+            priority: 10,
+            ..Default::default()
+        },
+        registry::RegistrySourceConfig {
+            name: "offline".to_string(),
+            location: format!("{}/apps.json", common::unreachable_url()),
+            priority: 5,
+            ..Default::default()
+        },
+        registry::RegistrySourceConfig {
+            name: "insecure".to_string(),
+            location: "http://example.com/apps.json".to_string(),
+            priority: 0,
+            ..Default::default()
+        },
+    ];
+
+    let cache_root = temp_dir.path().join("cache");
+    let (merged, statuses) = registry::fetch_registry_sources(&sources, &cache_root).unwrap();
+    let (apps, warnings) = registry::parse_registry(&merged).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(apps.len(), 2);
+
+    let browser = apps.iter().find(|app| app.name == "Browser").unwrap();
+    assert_eq!(browser.config.mcp_key, "browser-internal");
+    assert_eq!(browser.source.as_deref(), Some("internal"));
+    let time = apps.iter().find(|app| app.name == "Time").unwrap();
+    assert_eq!(time.source.as_deref(), Some("public"));
+
+    let status = |name: &str| statuses.iter().find(|s| s.name == name).unwrap();
+    assert_eq!(status("internal").app_count, 1);
+    assert_eq!(status("public").app_count, 1);
+    assert!(status("offline").error.is_some());
+    assert!(status("offline").source.is_none());
+    assert!(status("insecure").error.as_ref().unwrap().contains("HTTPS"));
+
+    // Names that only differ in punctuation don't share a cache
+    assert_ne!(
+        registry::source_cache_dir(&cache_root, "a.b"),
+        registry::source_cache_dir(&cache_root, "a_b")
+    );
+}
+
+#[test]
+fn test_registry_sources_all_unreachable() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sources = vec![registry::RegistrySourceConfig {
+        name: "offline".to_string(),
+        location: format!("{}/apps.json", common::unreachable_url()),
+        priority: 0,
+        ..Default::default()
+    }];
+    assert!(registry::fetch_registry_sources(&sources, temp_dir.path()).is_err());
+}
+
+#[test]
+fn test_invalid_entry_does_not_shadow_lower_priority_source() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, body: serde_json::Value| {
+        let path = temp_dir.path().join(name);
+        std::fs::write(&path, body.to_string()).unwrap();
+        path.to_string_lossy().to_string()
+    };
+
+    let sources = vec![
+        registry::RegistrySourceConfig {
+            name: "internal".to_string(),
+            location: write(
+                "internal.json",
+                json!([{ "name": "Time", "config": { "runtime": "npx", "args": [] } }]),
+            ),
+            priority: 10,
+            ..Default::default()
+        },
+        registry::RegistrySourceConfig {
+            name: "public".to_string(),
+            location: write(
+                "public.json",
+                json!([{
+                    "name": "Time",
+                    "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] }
+                }]),
+            ),
+            priority: 0,
+            ..Default::default()
+        },
+    ];
+
+    let (merged, _) = registry::fetch_registry_sources(&sources, temp_dir.path()).unwrap();
+    let (apps, warnings) = registry::parse_registry(&merged).unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].source.as_deref(), Some("public"));
+    assert_eq!(warnings.len(), 1);
+}
+
+const SIGNED_REGISTRY: &str = r#"[{"name":"Time","description":"Time server","config":{"mcpKey":"time","runtime":"npx","args":["-y","mcp-server-time"]}}]"#;
+const SIGNED_REGISTRY_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUTbJtuFzA1zJKv19I/OrIB+ZBrBFJLf9Wa96h6tNfg+VEEthmyNgaq9dgCUvlifZV22/oqEbyNtkFGvVTpzdhtBa9mKsoaxAAg=
+trusted comment: timestamp:1700000000
+PJGcRD8CPv9fIDaLuv1YbwKO9Px3zBbBIjkpJdQa0q8o2wFnLEofe6/Gmf7UtjMj7oGgGdQFcltnrBHthpcMAg==
+";
+// SIGNED_REGISTRY with "Evil server", signed by OTHER_PUBLIC_KEY
+const TAMPERED_REGISTRY_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RURT/13OZgMvcSPlRMyvwSOUWg8mopjIoWzukQPEiBuHnmScnUclRH+gBjlJ30J6s4GD7aig8LYzs8DQb9wWzHsFpIaVHq6irAo=
+trusted comment: timestamp:1700000000
+iFkcfs8kf1i1IekLCD8W9rdRrTc/i16nqD6pFgXVEryz7fSYiYKGgI4aJ2gejflCv+cPyhojW8550C+YvqP2Dw==
+";
+const REGISTRY_PUBLIC_KEY: &str = "RWTbJtuFzA1zJFIebBokkBC8w1cZI/oMqRCYLcJzKllLbkObwbE8VEn2";
+const OTHER_PUBLIC_KEY: &str = "RWRT/13OZgMvceIJaHXCaKCZPyD6S2bhX/ZR6eVc0sCN+7JOH0B5wUW6";
+
+fn signed_source(name: &str, location: String, public_key: &str) -> registry::RegistrySourceConfig {
+    registry::RegistrySourceConfig {
+        name: name.to_string(),
+        location,
+        priority: 0,
+        signed: true,
+        public_keys: vec![signature::TrustedKey {
+            name: "Fleur Team".to_string(),
+            public_key: public_key.to_string(),
+        }],
+    }
+}
+
+fn write_registry(
+    dir: &std::path::Path,
+    name: &str,
+    body: &str,
+    signature: Option<&str>,
+) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, body).unwrap();
+    if let Some(signature) = signature {
+        std::fs::write(dir.join(format!("{}.minisig", name)), signature).unwrap();
+    }
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_signed_file_sources() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+    let tampered = SIGNED_REGISTRY.replace("mcp-server-time", "evil-package");
+
+    let sources = vec![
+        signed_source(
+            "signed",
+            write_registry(
+                dir,
+                "signed.json",
+                SIGNED_REGISTRY,
+                Some(SIGNED_REGISTRY_SIGNATURE),
+            ),
+            REGISTRY_PUBLIC_KEY,
+        ),
+        signed_source(
+            "tampered",
+            write_registry(
+                dir,
+                "tampered.json",
+                &tampered,
+                Some(SIGNED_REGISTRY_SIGNATURE),
+            ),
+            REGISTRY_PUBLIC_KEY,
+        ),
+        signed_source(
+            "unsigned",
+            write_registry(dir, "unsigned.json", SIGNED_REGISTRY, None),
+            REGISTRY_PUBLIC_KEY,
+        ),
+        signed_source(
+            "wrong-key",
+            write_registry(
+                dir,
+                "wrong-key.json",
+                SIGNED_REGISTRY,
+                Some(SIGNED_REGISTRY_SIGNATURE),
+            ),
+            OTHER_PUBLIC_KEY,
+        ),
+    ];
+
+    let (merged, statuses) =
+        registry::fetch_registry_sources(&sources, &dir.join("cache")).unwrap();
+    let (apps, _) = registry::parse_registry(&merged).unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].source.as_deref(), Some("signed"));
+    assert_eq!(apps[0].signer.as_deref(), Some("Fleur Team"));
+    assert_eq!(apps[0].config.args[1], "mcp-server-time");
+
+    let status = |name: &str| statuses.iter().find(|s| s.name == name).unwrap();
+    assert_eq!(status("signed").signers, vec!["Fleur Team".to_string()]);
+    for name in ["tampered", "unsigned", "wrong-key"] {
+        assert!(status(name).source.is_none(), "{} was trusted", name);
+        assert!(status(name).error.as_ref().unwrap().contains("Refusing"));
+    }
+}
+
+#[test]
+fn test_unsigned_source_cannot_claim_signer() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let body = json!([{
+        "name": "Time",
+        "signer": "Fleur Team",
+        "config": { "mcpKey": "time", "runtime": "npx", "args": [] }
+    }])
+    .to_string();
+    let sources = vec![registry::RegistrySourceConfig {
+        name: "community".to_string(),
+        location: write_registry(temp_dir.path(), "apps.json", &body, None),
+        ..Default::default()
+    }];
+
+    let (merged, _) = registry::fetch_registry_sources(&sources, temp_dir.path()).unwrap();
+    let (apps, _) = registry::parse_registry(&merged).unwrap();
+    assert!(apps[0].signer.is_none());
+}
+
+#[test]
+fn test_signed_url_source_refuses_tampered_registry() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let tampered = Arc::new(AtomicBool::new(false));
+    let resigned = Arc::new(AtomicBool::new(false));
+    let signature_requests = Arc::new(AtomicUsize::new(0));
+    let (serve_tampered, serve_resigned, count_signatures) = (
+        tampered.clone(),
+        resigned.clone(),
+        signature_requests.clone(),
+    );
+    let server = common::start_http_server(move |request| match request.path.as_str() {
+        "/apps.json" if serve_tampered.load(Ordering::SeqCst) => {
+            common::TestHttpResponse::ok(SIGNED_REGISTRY.replace("Time server", "Evil server"))
+        }
+        "/apps.json" | "/unsigned.json" => common::TestHttpResponse::ok(SIGNED_REGISTRY),
+        "/apps.json.minisig" => {
+            count_signatures.fetch_add(1, Ordering::SeqCst);
+            if serve_resigned.load(Ordering::SeqCst) {
+                common::TestHttpResponse::ok(TAMPERED_REGISTRY_SIGNATURE)
+            } else {
+                common::TestHttpResponse::ok(SIGNED_REGISTRY_SIGNATURE)
+            }
+        }
+        _ => common::TestHttpResponse::status(404),
+    });
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    let source = signed_source(
+        "fleur",
+        format!("{}/apps.json", server.url),
+        REGISTRY_PUBLIC_KEY,
+    );
+    let (merged, statuses) =
+        registry::fetch_registry_sources(std::slice::from_ref(&source), cache_dir.path()).unwrap();
+    assert_eq!(merged[0]["signer"], "Fleur Team");
+    assert_eq!(statuses[0].source, Some(registry::RegistrySource::Network));
+    assert_eq!(signature_requests.load(Ordering::SeqCst), 1);
+
+    // The tampered document is refused and the last verified copy stays in use
+    tampered.store(true, Ordering::SeqCst);
+    let (merged, statuses) =
+        registry::fetch_registry_sources(std::slice::from_ref(&source), cache_dir.path()).unwrap();
+    assert_eq!(merged[0]["description"], "Time server");
+    assert_eq!(merged[0]["signer"], "Fleur Team");
+    assert_eq!(statuses[0].source, Some(registry::RegistrySource::Cache));
+    let error = statuses[0].error.as_ref().unwrap();
+    assert!(
+        error.contains("Refusing registry from 'fleur'"),
+        "{}",
+        error
+    );
+    let cached = std::fs::read_to_string(
+        registry::source_cache_dir(cache_dir.path(), "fleur").join("registry.json"),
+    )
+    .unwrap();
+    assert_eq!(cached, SIGNED_REGISTRY);
+
+    // A new signature from another key can't vouch for the tampered document,
+    // and the cached copy is still checked against the signature stored with it
+    resigned.store(true, Ordering::SeqCst);
+    let (merged, statuses) =
+        registry::fetch_registry_sources(std::slice::from_ref(&source), cache_dir.path()).unwrap();
+    assert_eq!(merged[0]["description"], "Time server");
+    assert_eq!(merged[0]["signer"], "Fleur Team");
+    assert_eq!(statuses[0].source, Some(registry::RegistrySource::Cache));
+    assert!(statuses[0]
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("Refusing registry from 'fleur'"));
+    assert_eq!(signature_requests.load(Ordering::SeqCst), 3);
+
+    // Without a verified copy to fall back to the source fails
+    let fresh_cache = tempfile::tempdir().unwrap();
+    let error = registry::fetch_registry_sources(&[source], fresh_cache.path()).unwrap_err();
+    assert!(
+        error.contains("Refusing registry from 'fleur'"),
+        "{}",
+        error
+    );
+
+    let unsigned = signed_source(
+        "unsigned",
+        format!("{}/unsigned.json", server.url),
+        REGISTRY_PUBLIC_KEY,
+    );
+    let error = registry::fetch_registry_sources(&[unsigned], cache_dir.path()).unwrap_err();
+    assert!(error.contains("Refusing unsigned registry"), "{}", error);
+}
+
+#[test]
+fn test_diff_registry() {
+    let (previous, _) = registry::parse_registry(&json!([
+        { "name": "Browser", "config": { "mcpKey": "puppeteer", "runtime": "npx", "args": ["-y", "puppeteer"] } },
+        { "name": "Time", "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] } }
+    ]))
+    .unwrap();
+    let (current, _) = registry::parse_registry(&json!([
+        { "name": "Time", "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time@2"] } },
+        { "name": "Gmail", "config": { "mcpKey": "gmail", "runtime": "npx", "args": ["-y", "gmail-mcp"] } }
+    ]))
+    .unwrap();
+
+    let delta = registry::diff_registry(&previous, &current);
+    assert_eq!(delta.added, vec!["Gmail"]);
+    assert_eq!(delta.removed, vec!["Browser"]);
+    assert_eq!(delta.changed, vec!["Time"]);
+    assert!(registry::diff_registry(&current, &current).is_empty());
+}
+
+#[test]
+#[serial]
+fn test_refresh_registry_reports_delta() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let registry_file = temp_dir.path().join("apps.json");
+    std::fs::write(
+        &registry_file,
+        json!([{ "name": "Time", "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] } }])
+            .to_string(),
+    )
+    .unwrap();
+
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+    settings::save_setting(
+        "registrySources",
+        json!([{ "name": "local", "location": registry_file.to_string_lossy() }]),
+    )
+    .unwrap();
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+
+    // Without a previous registry there is nothing to compare against
+    assert!(registry::refresh_registry().unwrap().is_empty());
+
+    std::fs::write(
+        &registry_file,
+        json!([{ "name": "Gmail", "config": { "mcpKey": "gmail", "runtime": "npx", "args": ["-y", "gmail-mcp"] } }])
+            .to_string(),
+    )
+    .unwrap();
+    let delta = registry::refresh_registry().unwrap();
+    assert_eq!(delta.added, vec!["Gmail"]);
+    assert_eq!(delta.removed, vec!["Time"]);
+    assert_eq!(app::get_app_registry().unwrap()[0]["name"], "Gmail");
+
+    settings::save_setting("registryRefreshInterval", json!(0)).unwrap();
+    assert!(registry::get_refresh_interval().is_none());
+    settings::save_setting("registryRefreshInterval", json!(5)).unwrap();
+    assert_eq!(
+        registry::get_refresh_interval(),
+        Some(std::time::Duration::from_secs(60))
+    );
+
+    // Cleanup
+    settings::set_test_settings_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
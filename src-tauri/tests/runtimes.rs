@@ -0,0 +1,269 @@
+mod common;
+
+use common::TestHttpResponse;
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    binaries,
+    clients::ClientType,
+    environment::{self, EnvironmentState, SetupProgress, SetupStage},
+    runtimes::{self, InstallError, PinnedArchive, BUN, DENO},
+};
+use serde_json::json;
+use serial_test::serial;
+use std::path::Path;
+use std::process::Command;
+
+// A tar.gz laid out like the Node.js releases, with everything in one folder
+fn runtime_archive() -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let bin_dir = dir.path().join("node-v20.9.0-test").join("bin");
+    std::fs::create_dir_all(&bin_dir).unwrap();
+    std::fs::write(bin_dir.join("node"), "#!/bin/sh\necho v20.9.0\n").unwrap();
+    let status = Command::new("tar")
+        .args(["-czf", "node.tar.gz", "node-v20.9.0-test"])
+        .current_dir(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    std::fs::read(dir.path().join("node.tar.gz")).unwrap()
+}
+
+#[test]
+fn test_managed_runtimes() {
+    assert_eq!(runtimes::managed_runtime("bunx").unwrap().name, "bun");
+    assert_eq!(runtimes::managed_runtime("deno").unwrap().name, "deno");
+    assert!(runtimes::managed_runtime("npx").is_none());
+
+    assert!(BUN.executable_path().ends_with(
+        Path::new("runtimes/bun")
+            .join(BUN.version)
+            .join(if cfg!(windows) { "bun.exe" } else { "bun" })
+    ));
+
+    let bunx_shim = BUN.shim_script(Path::new("/fleur/runtimes/bun/1.1.38/bin/bun"));
+    assert!(bunx_shim.contains("/fleur/runtimes/bun/1.1.38/bin/bun"));
+    #[cfg(not(windows))]
+    {
+        assert!(bunx_shim.starts_with("#!/bin/sh"));
+        assert!(bunx_shim.contains(r#"exec "$RUNTIME" x "$@""#));
+        let deno_shim = DENO.shim_script(Path::new("/fleur/runtimes/deno/2.1.4/bin/deno"));
+        assert!(deno_shim.contains(r#"exec "$RUNTIME" "$@""#));
+    }
+}
+
+#[test]
+fn test_pinned_runtime_archives() {
+    for (runtime, version) in [
+        ("node", "20.9.0"),
+        ("node", "22.12.0"),
+        ("uv", "0.5.11"),
+        (BUN.name, BUN.version),
+        (DENO.name, DENO.version),
+    ] {
+        for target in ["macos-aarch64", "macos-x86_64", "windows-x86_64"] {
+            let archive = runtimes::pinned_archive(runtime, version, target)
+                .unwrap_or_else(|| panic!("{} {} isn't pinned for {}", runtime, version, target));
+            assert!(archive.url.starts_with("https://"), "{}", archive.url);
+            assert!(archive.url.contains(version), "{}", archive.url);
+            assert!(
+                binaries::is_valid_sha256(&archive.sha256),
+                "{} {} for {} has no valid sha256 pinned",
+                runtime,
+                version,
+                target
+            );
+        }
+    }
+    assert!(runtimes::pinned_archive("node", "0.0.1", "macos-aarch64").is_none());
+}
+
+// Installs refuse archives without a digest, so every manifest entry needs one
+#[test]
+fn test_manifest_pins_every_digest() {
+    let manifest: serde_json::Value =
+        serde_json::from_str(include_str!("../runtimes.json")).unwrap();
+    for (runtime, versions) in manifest.as_object().unwrap() {
+        for (version, targets) in versions.as_object().unwrap() {
+            for (target, archive) in targets.as_object().unwrap() {
+                assert!(
+                    binaries::is_valid_sha256(archive["sha256"].as_str().unwrap_or_default()),
+                    "{} {} for {} has no sha256, run scripts/update-runtime-checksums",
+                    runtime,
+                    version,
+                    target
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_select_version() {
+    assert_eq!(
+        runtimes::select_version("node", None, "20.9.0").unwrap(),
+        "20.9.0"
+    );
+    assert_eq!(
+        runtimes::select_version("node", Some(">=18"), "20.9.0").unwrap(),
+        "20.9.0"
+    );
+    assert_eq!(
+        runtimes::select_version("node", Some(">=22"), "20.9.0").unwrap(),
+        "22.12.0"
+    );
+    assert!(runtimes::select_version("node", Some(">=99"), "20.9.0").is_err());
+    assert!(runtimes::select_version("node", Some("latest"), "20.9.0").is_err());
+}
+
+#[test]
+fn test_install_archive_stages() {
+    let archive = runtime_archive();
+    let sha256 = binaries::sha256_hex(&archive);
+    let server = common::start_http_server(move |request| match request.path.as_str() {
+        "/node.tar.gz" => TestHttpResponse::ok(archive.clone()),
+        "/garbage.tar.gz" => TestHttpResponse::ok(b"not an archive".to_vec()),
+        _ => TestHttpResponse::status(404),
+    });
+    let runtimes_dir = tempfile::tempdir().unwrap();
+    let install_dir = runtimes_dir.path().join("node").join("20.9.0");
+    let node = Path::new("bin/node");
+    let pinned = |path: &str, sha256: &str| PinnedArchive {
+        url: format!("{}{}", server.url, path),
+        sha256: sha256.to_string(),
+    };
+
+    let error = runtimes::install_archive(
+        "node",
+        &pinned("/missing.tar.gz", &sha256),
+        &install_dir,
+        node,
+    )
+    .unwrap_err();
+    assert_eq!(error.stage(), "download");
+    assert!(error.to_string().contains("404"), "{}", error);
+
+    let error = runtimes::install_archive(
+        "node",
+        &pinned("/node.tar.gz", &"0".repeat(64)),
+        &install_dir,
+        node,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        InstallError::Checksum {
+            runtime: "node".to_string(),
+            expected: "0".repeat(64),
+            actual: sha256.clone(),
+        }
+    );
+    let error = runtimes::install_archive("node", &pinned("/node.tar.gz", ""), &install_dir, node)
+        .unwrap_err();
+    assert_eq!(error.stage(), "checksum");
+    assert!(!install_dir.exists());
+    // Installs outside a setup run don't mark the environment setup as failed
+    assert!(!matches!(
+        environment::get_environment_status().last_progress,
+        Some(SetupProgress {
+            stage: SetupStage::Failed,
+            ..
+        })
+    ));
+
+    // During a setup run the failure is what the environment status shows
+    let setup_error = environment::environment_manager()
+        .ensure_ready_with(|| {
+            runtimes::install_archive("node", &pinned("/node.tar.gz", ""), &install_dir, node)
+                .map_err(String::from)
+        })
+        .unwrap_err();
+    assert!(setup_error.contains("checksum"), "{}", setup_error);
+    let status = environment::get_environment_status();
+    assert!(matches!(status.state, EnvironmentState::Failed(_)));
+    let failed = status.last_progress.unwrap();
+    assert_eq!(failed.stage, SetupStage::Failed);
+    assert!(failed.message.contains("checksum"), "{}", failed.message);
+
+    let garbage_sha256 = binaries::sha256_hex(b"not an archive");
+    let error = runtimes::install_archive(
+        "node",
+        &pinned("/garbage.tar.gz", &garbage_sha256),
+        &install_dir,
+        node,
+    )
+    .unwrap_err();
+    assert_eq!(error.stage(), "extract");
+    let error = runtimes::install_archive(
+        "node",
+        &pinned("/node.tar.gz", &sha256),
+        &install_dir,
+        Path::new("bin/deno"),
+    )
+    .unwrap_err();
+    assert_eq!(error.stage(), "extract");
+    assert!(!install_dir.exists());
+
+    // The single top level folder is unwrapped into the install directory
+    runtimes::install_archive(
+        "node",
+        &pinned("/node.tar.gz", &sha256.to_uppercase()),
+        &install_dir,
+        node,
+    )
+    .unwrap();
+    assert!(install_dir.join("bin/node").is_file());
+    let leftovers: Vec<_> = std::fs::read_dir(runtimes_dir.path().join("node"))
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name())
+        .collect();
+    assert_eq!(leftovers, vec!["20.9.0"]);
+}
+
+#[test]
+#[serial]
+fn test_install_bunx_and_deno_apps() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Bun Server",
+                "config": { "mcpKey": "bun-server", "runtime": "bunx", "args": ["@acme/bun-mcp"] }
+            },
+            {
+                "name": "Deno Server",
+                "config": { "mcpKey": "deno-server", "runtime": "deno", "args": ["run", "-A", "jsr:@acme/mcp"] }
+            }
+        ]));
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app("Bun Server", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Deno Server", None, ClientType::Claude.as_str()).unwrap();
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let bun_server = &config["mcpServers"]["bun-server"];
+    assert_eq!(
+        bun_server["command"],
+        BUN.shim_path().to_string_lossy().as_ref()
+    );
+    assert_eq!(bun_server["args"], json!(["@acme/bun-mcp"]));
+    let deno_server = &config["mcpServers"]["deno-server"];
+    assert_eq!(
+        deno_server["command"],
+        DENO.shim_path().to_string_lossy().as_ref()
+    );
+    assert_eq!(deno_server["args"], json!(["run", "-A", "jsr:@acme/mcp"]));
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
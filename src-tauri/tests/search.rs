@@ -0,0 +1,176 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    environment, registry,
+    search::{self, SearchFilters, SearchIndex},
+};
+use serde_json::json;
+use serial_test::serial;
+
+fn test_registry() -> serde_json::Value {
+    json!([
+        {
+            "name": "Browser",
+            "description": "Control a headless browser",
+            "category": "Utilities",
+            "developer": "Anthropic",
+            "config": { "mcpKey": "puppeteer", "runtime": "npx", "args": ["-y", "@modelcontextprotocol/server-puppeteer"] }
+        },
+        {
+            "name": "Time",
+            "description": "Time and timezone conversion",
+            "category": "Utilities",
+            "developer": "Anthropic",
+            "config": { "mcpKey": "time", "runtime": "npx", "args": ["-y", "mcp-server-time"] }
+        },
+        {
+            "name": "Gmail",
+            "description": "Read and send email",
+            "category": "Productivity",
+            "developer": "Google",
+            "config": { "mcpKey": "gmail", "runtime": "npx", "args": ["-y", "gmail-mcp"] }
+        },
+        {
+            "name": "Google Calendar",
+            "description": "Manage calendar events",
+            "category": "Productivity",
+            "developer": "Google",
+            "config": { "mcpKey": "calendar", "runtime": "npx", "args": ["-y", "calendar-mcp"] }
+        }
+    ])
+}
+
+fn test_index() -> SearchIndex {
+    let (apps, _) = registry::parse_registry(&test_registry()).unwrap();
+    SearchIndex::new(&apps)
+}
+
+fn names(results: &search::SearchResults) -> Vec<&str> {
+    results
+        .results
+        .iter()
+        .map(|r| r.app.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_search_ranks_and_tolerates_typos() {
+    let index = test_index();
+    let filters = SearchFilters::default();
+
+    // An empty query lists every app alphabetically
+    let all = index.search("", &filters, None);
+    assert_eq!(
+        names(&all),
+        vec!["Browser", "Gmail", "Google Calendar", "Time"]
+    );
+
+    // Name matches outrank description matches
+    let results = index.search("calendar", &filters, None);
+    assert_eq!(names(&results), vec!["Google Calendar"]);
+
+    let results = index.search("time", &filters, None);
+    assert_eq!(names(&results)[0], "Time");
+
+    // Typos and partially typed words still find the app
+    assert_eq!(
+        names(&index.search("calender", &filters, None)),
+        vec!["Google Calendar"]
+    );
+    assert_eq!(
+        names(&index.search("brwoser", &filters, None)),
+        vec!["Browser"]
+    );
+    assert_eq!(names(&index.search("gma", &filters, None)), vec!["Gmail"]);
+
+    // Every word of the query has to match
+    assert_eq!(
+        names(&index.search("google email", &filters, None)),
+        vec!["Gmail"]
+    );
+    assert!(index.search("xyzzy", &filters, None).results.is_empty());
+}
+
+#[test]
+fn test_search_facets_and_filters() {
+    let index = test_index();
+
+    let results = index.search("", &SearchFilters::default(), None);
+    assert_eq!(results.facets.categories.len(), 2);
+    assert_eq!(results.facets.developers[0].count, 2);
+
+    let filters = SearchFilters {
+        categories: vec!["productivity".to_string()],
+        ..Default::default()
+    };
+    let results = index.search("", &filters, None);
+    assert_eq!(names(&results), vec!["Gmail", "Google Calendar"]);
+    // Facets ignore the facet filters so other values stay selectable
+    assert_eq!(results.facets.categories.len(), 2);
+
+    let filters = SearchFilters {
+        developers: vec!["Anthropic".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        names(&index.search("", &filters, None)),
+        vec!["Browser", "Time"]
+    );
+}
+
+#[test]
+#[serial]
+fn test_search_apps_installed_filter() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(test_registry());
+    }
+
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path));
+    app::install_app("Time", None, ClientType::Claude.as_str()).unwrap();
+
+    let installed = search::search_apps(
+        "",
+        Some(SearchFilters {
+            client: Some(ClientType::Claude.as_str().to_string()),
+            installed: Some(true),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(names(&installed), vec!["Time"]);
+    assert_eq!(installed.results[0].installed, Some(true));
+
+    let not_installed = search::search_apps(
+        "goo",
+        Some(SearchFilters {
+            client: Some(ClientType::Claude.as_str().to_string()),
+            installed: Some(false),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(names(&not_installed), vec!["Google Calendar", "Gmail"]);
+
+    // The installed filter needs to know which client to look at
+    let missing_client = search::search_apps(
+        "",
+        Some(SearchFilters {
+            installed: Some(true),
+            ..Default::default()
+        }),
+    );
+    assert!(missing_client.is_err());
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    environment,
+    runtimes::BUN,
+    settings, shell_env,
+};
+use serde_json::json;
+use serial_test::serial;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_parse_env_output() {
+    let output = b"Welcome back!\n\x1b]0;zsh\x07__FLEUR_SHELL_ENV__\0PATH=/opt/homebrew/bin:/usr/bin\0MULTI=one\ntwo\0EMPTY=\0not a variable\0";
+    let env = shell_env::parse_env_output(output);
+    assert_eq!(env["PATH"], "/opt/homebrew/bin:/usr/bin");
+    assert_eq!(env["MULTI"], "one\ntwo");
+    assert_eq!(env["EMPTY"], "");
+    assert_eq!(env.len(), 3);
+
+    // Without the marker nothing can be trusted to be env output
+    assert!(shell_env::parse_env_output(b"PATH=/usr/bin\0").is_empty());
+
+    let selected = shell_env::select_variables(&env, &["PATH".to_string(), "GOPATH".to_string()]);
+    assert_eq!(selected.keys().collect::<Vec<_>>(), vec!["PATH"]);
+}
+
+#[test]
+fn test_capture_login_environment() {
+    if cfg!(windows) {
+        return;
+    }
+
+    let env = shell_env::capture_login_environment("/bin/sh").unwrap();
+    assert!(env.contains_key("PATH"));
+    assert!(shell_env::capture_login_environment("/does/not/exist").is_err());
+}
+
+#[test]
+#[serial]
+fn test_shims_export_captured_environment() {
+    if cfg!(windows) {
+        return;
+    }
+
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+
+    // Nothing is exported until the user opts in
+    let script = environment::uvx_shim_script("/fleur/uv/uvx");
+    assert!(script.contains("export PATH='/fleur/uv:/usr/bin:/bin:/usr/sbin:/sbin'"));
+    assert!(!script.contains("DOCKER_HOST"));
+
+    let captured = BTreeMap::from([
+        ("PATH".to_string(), "/opt/homebrew/bin:/usr/bin".to_string()),
+        (
+            "DOCKER_HOST".to_string(),
+            "unix:///var/run/docker.sock".to_string(),
+        ),
+        (
+            "GOFLAGS".to_string(),
+            r#"-ldflags='-s' "$HOME""#.to_string(),
+        ),
+        ("NOT A NAME".to_string(), "ignored".to_string()),
+    ]);
+    shell_env::store_environment(&captured).unwrap();
+    assert_eq!(shell_env::get_shell_environment(), captured);
+
+    let bunx_shim = BUN.shim_script(Path::new("/fleur/runtimes/bun/bin/bun"));
+    assert!(bunx_shim
+        .contains(r#"export PATH='/fleur/runtimes/bun/bin':'/opt/homebrew/bin:/usr/bin':"$PATH""#));
+    assert!(bunx_shim.contains("DOCKER_HOST"));
+
+    // Values reach the app verbatim and whatever the client sets wins
+    let shim = temp_dir.path().join("uvx-fleur");
+    std::fs::write(&shim, environment::uvx_shim_script("/usr/bin/env")).unwrap();
+    let output = Command::new("sh")
+        .arg(&shim)
+        .env("DOCKER_HOST", "tcp://remote:2375")
+        .env_remove("GOFLAGS")
+        .output()
+        .unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let env: BTreeMap<_, _> = output
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .collect();
+    assert_eq!(env["PATH"], "/usr/bin:/opt/homebrew/bin:/usr/bin");
+    assert_eq!(env["DOCKER_HOST"], "tcp://remote:2375");
+    assert_eq!(env["GOFLAGS"], r#"-ldflags='-s' "$HOME""#);
+    assert!(!output.contains("ignored"));
+
+    // Clearing takes the variables out of the shims again
+    shell_env::clear_environment().unwrap();
+    assert!(shell_env::get_shell_environment().is_empty());
+    assert!(settings::get_setting("shellEnvironment").is_none());
+    let script = environment::uvx_shim_script("/fleur/uv/uvx");
+    assert!(!script.contains("/opt/homebrew/bin"));
+    assert!(!script.contains("DOCKER_HOST"));
+
+    // Cleanup
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_entries_without_shim_get_captured_environment() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Time",
+                "config": { "mcpKey": "time", "runtime": "npx", "args": ["mcp-time"] }
+            },
+            {
+                "name": "Fetch",
+                "config": {
+                    "mcpKey": "fetch",
+                    "runtime": "docker",
+                    "args": [],
+                    "container": { "image": "mcp/fetch" }
+                }
+            }
+        ]));
+    }
+
+    let (config_path, _config_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+    let read_config = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap()
+    };
+
+    shell_env::store_environment(&BTreeMap::from([
+        ("PATH".to_string(), "/opt/homebrew/bin".to_string()),
+        ("DOCKER_HOST".to_string(), "unix:///docker.sock".to_string()),
+    ]))
+    .unwrap();
+    app::install_app("Time", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app(
+        "Fetch",
+        Some(json!({ "DOCKER_HOST": "tcp://remote:2375" })),
+        ClientType::Claude.as_str(),
+    )
+    .unwrap();
+
+    // What the user set wins, and captured values stay out of the container
+    let config = read_config();
+    let fetch = &config["mcpServers"]["fetch"];
+    assert_eq!(fetch["env"]["PATH"], "/opt/homebrew/bin");
+    assert_eq!(fetch["env"]["DOCKER_HOST"], "tcp://remote:2375");
+    assert!(!fetch["args"].as_array().unwrap().contains(&json!("PATH")));
+    assert!(config["mcpServers"]["time"]["env"].get("PATH").is_none());
+
+    // Capturing again updates installed entries
+    shell_env::store_environment(&BTreeMap::from([(
+        "PATH".to_string(),
+        "/usr/local/bin".to_string(),
+    )]))
+    .unwrap();
+    let config = read_config();
+    assert_eq!(
+        config["mcpServers"]["fetch"]["env"]["PATH"],
+        "/usr/local/bin"
+    );
+    assert!(app::get_outdated_apps(ClientType::Claude.as_str())
+        .unwrap()
+        .is_empty());
+
+    shell_env::clear_environment().unwrap();
+    let config = read_config();
+    assert!(config["mcpServers"]["fetch"]["env"].get("PATH").is_none());
+    assert_eq!(
+        config["mcpServers"]["fetch"]["env"]["DOCKER_HOST"],
+        "tcp://remote:2375"
+    );
+
+    // Cleanup
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
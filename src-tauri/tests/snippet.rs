@@ -0,0 +1,143 @@
+mod common;
+
+use fleur_lib::{app, clients::ClientType, environment, snippet};
+use serde_json::json;
+use serial_test::serial;
+
+const README_SNIPPET: &str = r#"{
+  "mcpServers": {
+    "github": {
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "env": {
+        "GITHUB_PERSONAL_ACCESS_TOKEN": "<YOUR_TOKEN>",
+      },
+    },
+    "filesystem": {
+      "command": "npx.cmd",
+      "args": ["-y", "@modelcontextprotocol/server-filesystem", "<path/to/allowed/dir>"]
+    },
+    "fetch": {
+      "command": "uvx",
+      "args": ["mcp-server-fetch", "--user-agent", "${USER_AGENT}"],
+      "env": { "USER_AGENT": "fleur" }
+    },
+    "local": {
+      "command": "/usr/local/bin/my-mcp"
+    }
+  }
+}"#;
+
+#[test]
+fn test_parse_snippet() {
+    let servers =
+        snippet::parse_snippet_with_paths(README_SNIPPET, "/fleur/npx-fleur", "/fleur/uvx")
+            .unwrap();
+    assert_eq!(servers.len(), 4);
+
+    let github = servers.iter().find(|s| s.key == "github").unwrap();
+    assert_eq!(github.resolved_command, "/fleur/npx-fleur");
+    assert_eq!(github.inputs.len(), 1);
+    assert_eq!(github.inputs[0].name, "GITHUB_PERSONAL_ACCESS_TOKEN");
+    assert_eq!(github.inputs[0].placeholder, "<YOUR_TOKEN>");
+
+    let filesystem = servers.iter().find(|s| s.key == "filesystem").unwrap();
+    assert_eq!(filesystem.resolved_command, "/fleur/npx-fleur");
+    assert_eq!(filesystem.inputs[0].name, "path/to/allowed/dir");
+
+    // References to env vars the snippet already sets aren't inputs
+    let fetch = servers.iter().find(|s| s.key == "fetch").unwrap();
+    assert_eq!(fetch.resolved_command, "/fleur/uvx");
+    assert!(fetch.inputs.is_empty());
+
+    let local = servers.iter().find(|s| s.key == "local").unwrap();
+    assert_eq!(local.resolved_command, "/usr/local/bin/my-mcp");
+    assert!(local.args.is_empty());
+
+    // The bare entries without the mcpServers wrapper are accepted too
+    let bare = r#""time": { "command": "uvx", "args": ["mcp-server-time"] }"#;
+    let servers = snippet::parse_snippet_with_paths(bare, "npx", "uvx").unwrap();
+    assert_eq!(servers[0].key, "time");
+
+    // Commas inside strings are kept even when a bracket follows them
+    let quoted =
+        r#"{"mcpServers": {"echo": {"command": "uvx", "args": ["echo", "a,]", "b\",}",],},},}"#;
+    let servers = snippet::parse_snippet_with_paths(quoted, "npx", "uvx").unwrap();
+    assert_eq!(servers[0].args, vec!["echo", "a,]", "b\",}"]);
+
+    assert!(snippet::parse_snippet_with_paths("not json", "npx", "uvx").is_err());
+    assert!(snippet::parse_snippet_with_paths(
+        r#"{"mcpServers": {"remote": {"url": "https://example.com/sse"}}}"#,
+        "npx",
+        "uvx"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_build_server_config() {
+    let servers = snippet::parse_snippet_with_paths(README_SNIPPET, "npx-fleur", "uvx").unwrap();
+    let fetch = servers.iter().find(|s| s.key == "fetch").unwrap();
+    let entry = snippet::build_server_config(fetch, &Default::default()).unwrap();
+    assert_eq!(
+        entry["args"],
+        json!(["mcp-server-fetch", "--user-agent", "fleur"])
+    );
+
+    let filesystem = servers.iter().find(|s| s.key == "filesystem").unwrap();
+    assert!(snippet::build_server_config(filesystem, &Default::default()).is_err());
+    let values = json!({ "path/to/allowed/dir": "/Users/me/Documents" });
+    let entry = snippet::build_server_config(filesystem, values.as_object().unwrap()).unwrap();
+    assert_eq!(
+        entry["args"],
+        json!([
+            "-y",
+            "@modelcontextprotocol/server-filesystem",
+            "/Users/me/Documents"
+        ])
+    );
+}
+
+#[test]
+#[serial]
+fn test_install_mcp_snippet() {
+    environment::set_test_mode(true);
+    let (config_path, _temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    // Nothing is written until every placeholder has a value
+    let result = snippet::install_mcp_snippet(
+        README_SNIPPET,
+        Some(json!({ "GITHUB_PERSONAL_ACCESS_TOKEN": "ghp_test" })),
+        ClientType::Claude.as_str(),
+    );
+    assert!(result.unwrap_err().contains("path/to/allowed/dir"));
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(config["mcpServers"].get("github").is_none());
+
+    let result = snippet::install_mcp_snippet(
+        README_SNIPPET,
+        Some(json!({
+            "GITHUB_PERSONAL_ACCESS_TOKEN": "ghp_test",
+            "path/to/allowed/dir": "/tmp"
+        })),
+        ClientType::Claude.as_str(),
+    );
+    assert!(result.is_ok(), "Snippet install failed: {:?}", result);
+
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let github = &config["mcpServers"]["github"];
+    assert_eq!(github["command"], "/test/.local/share/fleur/bin/npx-fleur");
+    assert_eq!(github["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_test");
+    assert_eq!(
+        config["mcpServers"]["fetch"]["command"],
+        "/test/.local/share/fleur/bin/uvx-fleur"
+    );
+    assert_eq!(config["mcpServers"]["filesystem"]["args"][2], "/tmp");
+
+    // Cleanup
+    app::set_test_config_path(None);
+    environment::set_test_mode(false);
+}
@@ -50,7 +50,7 @@ fn test_full_app_lifecycle() {
     }
 
     // Test installation
-    let install_result = app::install("Browser", None, ClientType::Cursor.as_str());
+    let install_result = app::install_app("Browser", None, ClientType::Cursor.as_str());
     assert!(
         install_result.is_ok(),
         "Install failed: {:?}",
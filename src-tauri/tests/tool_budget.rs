@@ -0,0 +1,137 @@
+mod common;
+
+use fleur_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    environment, settings,
+    tool_budget::{self, BudgetWarningKind, ToolBudget},
+};
+use serde_json::json;
+use serial_test::serial;
+use std::path::{Path, PathBuf};
+
+fn write_fake_mcp_server(dir: &Path, tool_count: usize) -> PathBuf {
+    let tools: Vec<_> = (0..tool_count)
+        .map(|i| {
+            json!({
+                "name": format!("tool_{}", i),
+                "description": "Does something useful",
+                "inputSchema": { "type": "object", "properties": {} }
+            })
+        })
+        .collect();
+    let tools_response = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "result": { "tools": tools }
+    });
+
+    let script = format!(
+        r#"while IFS= read -r line; do
+  case "$line" in
+    *'"initialize"'*) echo '{{"jsonrpc":"2.0","id":1,"result":{{"capabilities":{{}}}}}}' ;;
+    *'"tools/list"'*) echo '{}' ;;
+  esac
+done
+"#,
+        tools_response
+    );
+
+    let script_path = dir.join(format!("server_{}.sh", tool_count));
+    std::fs::write(&script_path, script).unwrap();
+    script_path
+}
+
+#[test]
+fn test_estimate_tool_tokens() {
+    let tool = json!({ "name": "abcd" });
+    let serialized_len = serde_json::to_string(&tool).unwrap().len();
+    assert_eq!(
+        tool_budget::estimate_tool_tokens(&tool),
+        serialized_len.div_ceil(4)
+    );
+}
+
+#[test]
+fn test_budget_warnings() {
+    let budget = ToolBudget::default();
+    assert!(tool_budget::budget_warnings(&ClientType::Claude, &budget, 500, 100_000).is_empty());
+
+    let warnings = tool_budget::budget_warnings(&ClientType::Cursor, &budget, 41, 0);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, BudgetWarningKind::ClientLimit);
+
+    let budget = ToolBudget {
+        max_tools: Some(10),
+        max_tokens: Some(1000),
+    };
+    let warnings = tool_budget::budget_warnings(&ClientType::Claude, &budget, 11, 1001);
+    assert_eq!(warnings.len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_tool_budget_report_and_install_check() {
+    environment::set_test_mode(true);
+    tool_budget::clear_tool_cache();
+
+    let (config_path, temp_dir) = common::setup_test_config();
+    app::set_test_config_path(Some(config_path.clone()));
+    let settings_path = temp_dir.path().join("settings.json");
+    settings::set_test_settings_path(Some(settings_path));
+    settings::save_setting("toolBudget", json!({ "maxTools": 5 })).unwrap();
+
+    let installed_server = write_fake_mcp_server(temp_dir.path(), 3);
+    let candidate_server = write_fake_mcp_server(temp_dir.path(), 4);
+
+    std::fs::write(
+        &config_path,
+        serde_json::to_string_pretty(&json!({
+            "mcpServers": {
+                "installed": {
+                    "command": "sh",
+                    "args": [installed_server.to_string_lossy()]
+                }
+            }
+        }))
+        .unwrap(),
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Candidate",
+            "description": "Candidate server",
+            "config": {
+                "mcpKey": "candidate",
+                "runtime": "sh",
+                "args": [candidate_server.to_string_lossy()]
+            }
+        }]));
+    }
+
+    let report = tool_budget::tool_budget_report(ClientType::Claude.as_str()).unwrap();
+    assert_eq!(report["totalTools"], 3);
+    assert_eq!(report["servers"][0]["mcpKey"], "installed");
+    assert_eq!(report["servers"][0]["tools"][0], "tool_0");
+    assert!(report["estimatedTokens"].as_u64().unwrap() > 0);
+    assert!(report["warnings"].as_array().unwrap().is_empty());
+
+    let check =
+        tool_budget::check_install_budget("Candidate", None, ClientType::Claude.as_str()).unwrap();
+    assert_eq!(check["toolCount"], 4);
+    assert_eq!(check["projectedTools"], 7);
+    assert_eq!(check["withinBudget"], false);
+    assert_eq!(check["warnings"][0]["kind"], "toolBudget");
+
+    // Cleanup
+    app::set_test_config_path(None);
+    settings::set_test_settings_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -14,6 +14,12 @@ export function AppInformation({ app }: AppInformationProps) {
           <dt className="text-gray-400 text-base mb-1">Developer</dt>
           <dd className="text-gray-900 text-lg">{app.developer}</dd>
         </div>
+        {app.signer && (
+          <div>
+            <dt className="text-gray-400 text-base mb-1">Signed by</dt>
+            <dd className="text-gray-900 text-lg">{app.signer}</dd>
+          </div>
+        )}
         <div>
           <dt className="text-gray-400 text-base mb-1">Category</dt>
           <dd className="text-gray-900 text-lg">{app.category}</dd>
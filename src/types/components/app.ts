@@ -18,6 +18,8 @@ export interface App {
   features?: Feature[];
   setup?: Setup[];
   envVars?: EnvVar[];
+  source?: string;
+  signer?: string;
 }
 
 interface Feature {